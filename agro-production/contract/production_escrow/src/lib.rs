@@ -64,14 +64,18 @@ pub enum EscrowError {
     CampaignDeadlinePassed = 30,
     CampaignDeadlineNotPassed = 31,
     CampaignAlreadyTerminal = 32,
-    CampaignNotFailedOrSettled = 33,
     CampaignNotFundedOrBeyond = 34,
 
     OrderNotFound = 40,
+    /// The order is past the stage the action needs. For split orders this
+    /// includes funding an order that is no longer `Funding`, formerly
+    /// `SplitOrderAlreadyFunded` (105).
     OrderNotPending = 41,
 
     NotAdmin = 50,
     NotFarmer = 51,
+    /// The caller is not the order's buyer, or the campaign's farmer was
+    /// named to take a buyer's place.
     NotBuyer = 52,
     NotInvestor = 53,
     /// Issue #652: caller was not the configured governance contract, once
@@ -86,36 +90,40 @@ pub enum EscrowError {
     InvalidGovernanceContract = 55,
 
     NothingToClaim = 60,
+    /// The investor has already claimed, or already transferred away, their
+    /// stake in the campaign. Also covers the former `AlreadyTransferred`
+    /// (85), merged here to stay under the 50-variant `contracterror` limit.
     AlreadyClaimed = 61,
 
-    TrancheAlreadyReleased = 70,
     InvalidTranche = 71,
-    InvalidResolution = 72,
+    /// A `Partial` dispute ruling or fee split whose basis points are out of
+    /// range or don't add up to 10,000.
+    InvalidSplitRatio = 72,
 
-    NoShortfall = 80,
     InvalidMilestone = 81,
     MilestoneNotConfigured = 82,
     MilestoneAlreadyAdvanced = 83,
     NotBuyerOrOracle = 84,
-    AlreadyTransferred = 85,
     SameInvestor = 86,
     ArbitrationNotConfigured = 90,
     ArbitratorNotFound = 91,
-    QuorumNotReached = 92,
     AlreadyVoted = 93,
-    InvestorNotInCampaign = 94,
     CancelWindowClosed = 95,
+    /// Arbitrator stake below the pool minimum, or an unstake that exceeds
+    /// the stake or would leave an arbitrator on an open panel under-staked.
+    InsufficientStake = 96,
+    /// Arbitration pool parameters out of range: a non-positive minimum
+    /// stake, a panel larger than `MAX_PANEL_SIZE`, or a quorum of zero or
+    /// above the panel size.
+    InvalidArbitrationConfig = 97,
     /// Issue #654: multi-party split order errors.
     SplitSharesMustSumToTotal = 100,
     SplitOrderNotFullyFunded = 101,
     NotCoBuyer = 102,
     AlreadyContributed = 103,
     AlreadyConfirmed = 104,
-    SplitOrderAlreadyFunded = 105,
     EmptyCoBuyerList = 106,
-    SplitOrderNotFound = 107,
     SplitOrderNotDisputed = 108,
-    SplitOrderAlreadyDisputed = 109,
 }
 
 // ---------------------------------------------------------------------------
@@ -246,6 +254,46 @@ pub struct SplitOrder {
     pub status: SplitOrderStatus,
 }
 
+/// Admin-set parameters of the staked arbitrator pool.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitrationConfig {
+    pub stake_token: Address,
+    /// Stake required to be drawn onto a dispute panel.
+    pub min_stake: i128,
+    pub panel_size: u32,
+    /// Panel votes for the same resolution needed to resolve a dispute.
+    pub quorum: u32,
+}
+
+/// An arbitrator's bonded stake and standing in the pool.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitratorStake {
+    pub token: Address,
+    pub amount: i128,
+    /// Unresolved campaign disputes this arbitrator sits on the panel of.
+    pub open_panels: u32,
+    /// Votes against the panel's consensus since the last dissent slash.
+    pub dissents: u32,
+}
+
+/// Arbitrators drawn to decide one campaign dispute.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitrationPanel {
+    pub members: Vec<Address>,
+    pub drawn_at: u64,
+}
+
+/// One beneficiary's cut of platform fee revenue, in basis points.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeShare {
+    pub beneficiary: Address,
+    pub bps: u32,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
@@ -260,6 +308,11 @@ pub enum DataKey {
     RegistryContract,
     FeeCollector,
     FeeRateBps,
+    /// `Vec<FeeShare>` splitting fee revenue; unset sends it all to the fee
+    /// collector.
+    FeeSplit,
+    /// Fee revenue claimable per `(beneficiary, token)`.
+    FeeBalance(Address, Address),
     CampaignCount,
     OrderCount,
     Campaign(u64),
@@ -273,10 +326,12 @@ pub enum DataKey {
     Order(u64),
     /// Per-campaign milestone release configuration (Vec<MilestoneConfig>).
     MilestoneConfigs(u64),
-    /// Arbitrator pool addresses.
+    /// Arbitrators currently staked at or above the pool minimum.
     Arbitrators,
-    /// Minimum number of arbitrator votes required to resolve.
-    Quorum,
+    ArbitrationConfig,
+    ArbitratorStake(Address),
+    /// Panel drawn for a campaign's open dispute.
+    DisputePanel(u64),
     /// Track per-arbitrator vote per-campaign-dispute resolution.
     ArbitratorVote(u64, Address),
     /// Multi-party split order (Issue #654).
@@ -309,18 +364,30 @@ pub const ORDER_EXPIRY_SECS: u64 = 96 * 3600;
 /// `ORDER_EXPIRY_SECS` window.
 pub const CANCEL_WINDOW_SECS: u64 = 30 * 60;
 
+/// Panel members who haven't voted this long after the panel was drawn are
+/// slashed when the dispute is resolved.
+pub const VOTING_WINDOW_SECS: u64 = 7 * 24 * 3600;
+
+const MAX_PANEL_SIZE: u32 = 9;
+/// Share of an arbitrator's stake forfeited to the fee collector per slash.
+const ARBITRATOR_SLASH_BPS: i128 = 1_000;
+/// Votes against consensus after which an arbitrator is slashed.
+const MAX_ARBITRATOR_DISSENTS: u32 = 3;
+/// Most beneficiaries `set_fee_split` accepts.
+const MAX_FEE_BENEFICIARIES: u32 = 10;
+
 /// The ordered sequence of milestones. Index 1 = Planted, 2 = Growing, etc.
 /// A campaign starts at milestone 0 (no milestones advanced).
 /// Validates that the given milestone index matches the expected milestone type.
 fn validate_milestone_order(idx: u32, milestone: &Milestone) -> bool {
-    match (idx, milestone) {
-        (0, Milestone::Planted) => true,
-        (1, Milestone::Growing) => true,
-        (2, Milestone::Harvested) => true,
-        (3, Milestone::Shipped) => true,
-        (4, Milestone::Delivered) => true,
-        _ => false,
-    }
+    matches!(
+        (idx, milestone),
+        (0, Milestone::Planted)
+            | (1, Milestone::Growing)
+            | (2, Milestone::Harvested)
+            | (3, Milestone::Shipped)
+            | (4, Milestone::Delivered)
+    )
 }
 
 // Event topic helpers.
@@ -351,7 +418,7 @@ impl ProductionEscrowContract {
         if env.storage().instance().has(&DataKey::Admin) {
             return Err(EscrowError::AlreadyInitialized);
         }
-        if supported_tokens.len() < 1 {
+        if supported_tokens.is_empty() {
             return Err(EscrowError::MustSupportOneToken);
         }
         if fee_rate_bps > 10000 {
@@ -406,6 +473,68 @@ impl ProductionEscrowContract {
         Ok(())
     }
 
+    /// Shares the platform fees of campaign orders between `shares`. Each
+    /// beneficiary may appear once and the basis points must total 10,000;
+    /// cuts build up in `get_fee_balance` until claimed. An empty list goes
+    /// back to paying the fee collector on confirmation.
+    pub fn set_fee_split(
+        env: Env,
+        admin_caller: Address,
+        shares: Vec<FeeShare>,
+    ) -> Result<(), EscrowError> {
+        admin_caller.require_auth();
+        require_governed_caller(&env, &admin_caller)?;
+        if shares.len() > MAX_FEE_BENEFICIARIES {
+            return Err(EscrowError::InvalidSplitRatio);
+        }
+        let mut beneficiaries: Vec<Address> = Vec::new(&env);
+        let mut total_bps: u32 = 0;
+        for share in shares.iter() {
+            if share.bps == 0 || beneficiaries.contains(&share.beneficiary) {
+                return Err(EscrowError::InvalidSplitRatio);
+            }
+            beneficiaries.push_back(share.beneficiary);
+            total_bps = total_bps.saturating_add(share.bps);
+        }
+        if !shares.is_empty() && total_bps != 10_000 {
+            return Err(EscrowError::InvalidSplitRatio);
+        }
+        env.storage().instance().set(&DataKey::FeeSplit, &shares);
+        Ok(())
+    }
+
+    pub fn get_fee_split(env: Env) -> Vec<FeeShare> {
+        read_fee_split(&env)
+    }
+
+    /// Sends `beneficiary` the `token` fees credited to it by the fee split
+    /// and returns how much was sent; zero if nothing has built up.
+    pub fn claim_fees(env: Env, beneficiary: Address, token: Address) -> Result<i128, EscrowError> {
+        beneficiary.require_auth();
+        let key = DataKey::FeeBalance(beneficiary.clone(), token.clone());
+        let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if balance > 0 {
+            env.storage().persistent().remove(&key);
+            token::Client::new(&env, &token).transfer(
+                &env.current_contract_address(),
+                &beneficiary,
+                &balance,
+            );
+            env.events().publish(
+                (symbol_short!("fee"), symbol_short!("claimed")),
+                (beneficiary, token, balance),
+            );
+        }
+        Ok(balance)
+    }
+
+    pub fn get_fee_balance(env: Env, beneficiary: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::FeeBalance(beneficiary, token))
+            .unwrap_or(0)
+    }
+
     /// Set (or update) the governance contract address. Admin-only for the
     /// initial bootstrap while no governance is configured (Issue #680); once
     /// set, `set_fee_config`/`set_registry_contract`/`update_supported_tokens`/
@@ -440,7 +569,7 @@ impl ProductionEscrowContract {
     ) -> Result<(), EscrowError> {
         admin_caller.require_auth();
         require_governed_caller(&env, &admin_caller)?;
-        if supported_tokens.len() < 1 {
+        if supported_tokens.is_empty() {
             return Err(EscrowError::MustSupportOneToken);
         }
         env.storage()
@@ -1000,7 +1129,7 @@ impl ProductionEscrowContract {
 
         let mut order = load_split_order(&env, order_id)?;
         if order.status != SplitOrderStatus::Funding {
-            return Err(EscrowError::SplitOrderAlreadyFunded);
+            return Err(EscrowError::OrderNotPending);
         }
         let share = order
             .shares
@@ -1033,6 +1162,60 @@ impl ProductionEscrowContract {
         Ok(())
     }
 
+    /// Passes `from`'s place in a split order, with its pledged share and
+    /// any funding already paid in, to `to`. Both must sign; `to` may not
+    /// already be a co-buyer or be the campaign's farmer. A confirmation
+    /// `from` already gave is dropped, so `to` has to confirm for itself.
+    pub fn transfer_split_share(
+        env: Env,
+        from: Address,
+        to: Address,
+        order_id: u64,
+    ) -> Result<(), EscrowError> {
+        from.require_auth();
+        to.require_auth();
+
+        let mut order = load_split_order(&env, order_id)?;
+        if order.status != SplitOrderStatus::Funding && order.status != SplitOrderStatus::Active {
+            return Err(EscrowError::OrderNotPending);
+        }
+        let share = order
+            .shares
+            .get(from.clone())
+            .ok_or(EscrowError::NotCoBuyer)?;
+        if order.shares.contains_key(to.clone()) {
+            return Err(EscrowError::AlreadyContributed);
+        }
+        if to == load_campaign(&env, order.campaign_id)?.farmer {
+            return Err(EscrowError::NotBuyer);
+        }
+
+        let index = order
+            .co_buyers
+            .first_index_of(&from)
+            .ok_or(EscrowError::NotCoBuyer)?;
+        order.co_buyers.set(index, to.clone());
+        order.shares.remove(from.clone());
+        order.shares.set(to.clone(), share);
+        if order.funded.get(from.clone()).unwrap_or(false) {
+            order.funded.set(to.clone(), true);
+        }
+        order.funded.remove(from.clone());
+        if order.confirmed.get(from.clone()).unwrap_or(false) {
+            order.confirmed_count -= 1;
+            order.confirmed_value = checked_sub(order.confirmed_value, share)?;
+        }
+        order.confirmed.remove(from.clone());
+        save_split_order(&env, order_id, &order);
+
+        env.events().publish(
+            (t_order(), symbol_short!("splitxfer")),
+            (order_id, from, to, share),
+        );
+
+        Ok(())
+    }
+
     /// A co-buyer confirms receipt. Revenue is credited to the campaign and
     /// the fee collected once either a strict majority-by-value of
     /// co-buyers have confirmed, or every co-buyer has (unanimous) —
@@ -1071,19 +1254,7 @@ impl ProductionEscrowContract {
                 campaign.total_revenue = checked_add(campaign.total_revenue, order.total_amount)?;
                 save_campaign(&env, &campaign);
             }
-            if order.fee > 0 {
-                if let Some(fee_collector) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Address>(&DataKey::FeeCollector)
-                {
-                    token::Client::new(&env, &campaign.token).transfer(
-                        &env.current_contract_address(),
-                        &fee_collector,
-                        &order.fee,
-                    );
-                }
-            }
+            collect_fee(&env, &campaign.token, order.fee)?;
             order.status = SplitOrderStatus::Confirmed;
             env.events().publish(
                 (t_order(), symbol_short!("splitcnf")),
@@ -1151,19 +1322,7 @@ impl ProductionEscrowContract {
                         checked_add(campaign.total_revenue, order.total_amount)?;
                     save_campaign(&env, &campaign);
                 }
-                if order.fee > 0 {
-                    if let Some(fee_collector) = env
-                        .storage()
-                        .instance()
-                        .get::<_, Address>(&DataKey::FeeCollector)
-                    {
-                        token::Client::new(&env, &campaign.token).transfer(
-                            &env.current_contract_address(),
-                            &fee_collector,
-                            &order.fee,
-                        );
-                    }
-                }
+                collect_fee(&env, &campaign.token, order.fee)?;
                 order.status = SplitOrderStatus::Confirmed;
             }
         }
@@ -1207,17 +1366,8 @@ impl ProductionEscrowContract {
         campaign.total_revenue = checked_add(campaign.total_revenue, order.amount)?;
         order.status = OrderStatus::Confirmed;
 
-        // Transfer fee to collector if fee > 0.
-        if order.fee > 0 {
-            if let Some(fee_collector) = env
-                .storage()
-                .instance()
-                .get::<_, Address>(&DataKey::FeeCollector)
-            {
-                let token_client = token::Client::new(&env, &campaign.token);
-                token_client.transfer(&env.current_contract_address(), &fee_collector, &order.fee);
-            }
-        }
+        // Transfer fee to collector (or accrue it to the fee split) if fee > 0.
+        collect_fee(&env, &campaign.token, order.fee)?;
 
         env.storage()
             .persistent()
@@ -1540,6 +1690,7 @@ impl ProductionEscrowContract {
         }
         campaign.status = CampaignStatus::Disputed;
         save_campaign(&env, &campaign);
+        draw_dispute_panel(&env, &campaign);
 
         env.events().publish(
             (t_campaign(), symbol_short!("disputed")),
@@ -1548,8 +1699,9 @@ impl ProductionEscrowContract {
         Ok(())
     }
 
-    /// Admin-only resolution. Falls back to admin if no arbitrator pool is configured.
-    /// If arbitrator pool exists, admin can still resolve directly.
+    /// Admin-only resolution. Falls back to admin if no panel could be drawn.
+    /// If a panel exists, admin can still resolve directly; panel members
+    /// who let the voting window lapse are slashed either way.
     pub fn resolve_dispute(
         env: Env,
         admin_caller: Address,
@@ -1567,7 +1719,7 @@ impl ProductionEscrowContract {
             return Err(EscrowError::CampaignNotDisputed);
         }
 
-        resolve_dispute_internal(&env, campaign_id, resolution)
+        resolve_dispute_internal(&env, campaign_id, resolution, Vec::new(&env))
     }
 
     // -----------------------------------------------------------------------
@@ -1752,7 +1904,7 @@ impl ProductionEscrowContract {
 
         let claim_key_from = DataKey::Claimed(campaign_id, from.clone());
         if env.storage().persistent().has(&claim_key_from) {
-            return Err(EscrowError::AlreadyTransferred);
+            return Err(EscrowError::AlreadyClaimed);
         }
 
         let to_contribution_key = DataKey::Contribution(campaign_id, to.clone());
@@ -1776,10 +1928,17 @@ impl ProductionEscrowContract {
     // Arbitrator Pool
     // -----------------------------------------------------------------------
 
-    pub fn set_arbitrators(
+    /// Arbitrators stake `stake_token` to join the pool; each campaign
+    /// dispute draws a random panel from it, excluding the campaign's farmer
+    /// and investors, and only that panel may vote. Replaces the admin-set
+    /// list of `set_arbitrators`, which is gone: `get_arbitrators` now lists
+    /// whoever is staked at or above `min_stake`.
+    pub fn set_arbitration_config(
         env: Env,
         admin_caller: Address,
-        arbitrators: Vec<Address>,
+        stake_token: Address,
+        min_stake: i128,
+        panel_size: u32,
         quorum: u32,
     ) -> Result<(), EscrowError> {
         admin_caller.require_auth();
@@ -1787,20 +1946,113 @@ impl ProductionEscrowContract {
         if admin_caller != admin {
             return Err(EscrowError::NotAdmin);
         }
-        if quorum == 0 || quorum > arbitrators.len() as u32 {
+        if min_stake <= 0 || panel_size > MAX_PANEL_SIZE || quorum == 0 || quorum > panel_size {
+            return Err(EscrowError::InvalidArbitrationConfig);
+        }
+        env.storage().instance().set(
+            &DataKey::ArbitrationConfig,
+            &ArbitrationConfig {
+                stake_token,
+                min_stake,
+                panel_size,
+                quorum,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get_arbitration_config(env: Env) -> Result<ArbitrationConfig, EscrowError> {
+        arbitration_config(&env)
+    }
+
+    pub fn stake_arbitrator(
+        env: Env,
+        arbitrator: Address,
+        amount: i128,
+    ) -> Result<(), EscrowError> {
+        arbitrator.require_auth();
+        let config = arbitration_config(&env)?;
+        if amount <= 0 {
+            return Err(EscrowError::InvalidAmount);
+        }
+        let mut stake = load_arbitrator_stake(&env, &arbitrator).unwrap_or(ArbitratorStake {
+            token: config.stake_token.clone(),
+            amount: 0,
+            open_panels: 0,
+            dissents: 0,
+        });
+        if stake.amount > 0 && stake.token != config.stake_token {
+            return Err(EscrowError::UnsupportedToken);
+        }
+        stake.token = config.stake_token.clone();
+        stake.amount = checked_add(stake.amount, amount)?;
+        save_arbitrator_stake(&env, &arbitrator, &stake, &config);
+
+        token::Client::new(&env, &stake.token).transfer(
+            &arbitrator,
+            &env.current_contract_address(),
+            &amount,
+        );
+        env.events().publish(
+            (symbol_short!("arb"), symbol_short!("staked")),
+            (arbitrator, amount, stake.amount),
+        );
+        Ok(())
+    }
+
+    pub fn unstake_arbitrator(
+        env: Env,
+        arbitrator: Address,
+        amount: i128,
+    ) -> Result<(), EscrowError> {
+        arbitrator.require_auth();
+        let config = arbitration_config(&env)?;
+        if amount <= 0 {
             return Err(EscrowError::InvalidAmount);
         }
-        env.storage().instance().set(&DataKey::Arbitrators, &arbitrators);
-        env.storage().instance().set(&DataKey::Quorum, &quorum);
+        let mut stake =
+            load_arbitrator_stake(&env, &arbitrator).ok_or(EscrowError::ArbitratorNotFound)?;
+        if amount > stake.amount {
+            return Err(EscrowError::InsufficientStake);
+        }
+        stake.amount = checked_sub(stake.amount, amount)?;
+        if stake.open_panels > 0 && stake.amount < config.min_stake {
+            return Err(EscrowError::InsufficientStake);
+        }
+        save_arbitrator_stake(&env, &arbitrator, &stake, &config);
+
+        token::Client::new(&env, &stake.token).transfer(
+            &env.current_contract_address(),
+            &arbitrator,
+            &amount,
+        );
+        env.events().publish(
+            (symbol_short!("arb"), symbol_short!("unstaked")),
+            (arbitrator, amount, stake.amount),
+        );
         Ok(())
     }
 
+    pub fn get_arbitrator_stake(
+        env: Env,
+        arbitrator: Address,
+    ) -> Result<ArbitratorStake, EscrowError> {
+        load_arbitrator_stake(&env, &arbitrator).ok_or(EscrowError::ArbitratorNotFound)
+    }
+
+    /// Arbitrators currently staked at or above `min_stake`.
     pub fn get_arbitrators(env: Env) -> Vec<Address> {
-        env.storage().instance().get(&DataKey::Arbitrators).unwrap_or_else(|| Vec::new(&env))
+        arbitrator_pool(&env)
     }
 
     pub fn get_quorum(env: Env) -> u32 {
-        env.storage().instance().get(&DataKey::Quorum).unwrap_or(0)
+        arbitration_config(&env).map(|c| c.quorum).unwrap_or(0)
+    }
+
+    pub fn get_dispute_panel(env: Env, campaign_id: u64) -> Option<ArbitrationPanel> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DisputePanel(campaign_id))
     }
 
     pub fn vote_to_resolve(
@@ -1811,20 +2063,22 @@ impl ProductionEscrowContract {
     ) -> Result<(), EscrowError> {
         arbitrator.require_auth();
 
-        let arbitrators: Vec<Address> = env.storage().instance()
-            .get(&DataKey::Arbitrators)
-            .ok_or(EscrowError::ArbitrationNotConfigured)?;
-
-        let is_valid_arbitrator = arbitrators.iter().any(|a| a == arbitrator);
-        if !is_valid_arbitrator {
-            return Err(EscrowError::ArbitratorNotFound);
-        }
+        let config = arbitration_config(&env)?;
 
         let campaign = load_campaign(&env, campaign_id)?;
         if campaign.status != CampaignStatus::Disputed {
             return Err(EscrowError::CampaignNotDisputed);
         }
 
+        let panel: ArbitrationPanel = env
+            .storage()
+            .persistent()
+            .get(&DataKey::DisputePanel(campaign_id))
+            .ok_or(EscrowError::ArbitrationNotConfigured)?;
+        if !panel.members.contains(&arbitrator) {
+            return Err(EscrowError::ArbitratorNotFound);
+        }
+
         let vote_key = DataKey::ArbitratorVote(campaign_id, arbitrator.clone());
         if env.storage().persistent().has(&vote_key) {
             return Err(EscrowError::AlreadyVoted);
@@ -1832,16 +2086,20 @@ impl ProductionEscrowContract {
 
         env.storage().persistent().set(&vote_key, &resolution);
 
-        let quorum: u32 = env.storage().instance().get(&DataKey::Quorum).unwrap_or(0);
-        let mut yes_votes: u32 = 0;
-        for a in arbitrators.iter() {
-            if env.storage().persistent().has(&DataKey::ArbitratorVote(campaign_id, a)) {
-                yes_votes += 1;
+        // Only votes for this exact resolution count towards quorum.
+        let mut majority: Vec<Address> = Vec::new(&env);
+        for a in panel.members.iter() {
+            let vote: Option<DisputeResolution> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::ArbitratorVote(campaign_id, a.clone()));
+            if vote == Some(resolution.clone()) {
+                majority.push_back(a);
             }
         }
 
-        if yes_votes >= quorum {
-            resolve_dispute_internal(&env, campaign_id, resolution)?;
+        if majority.len() >= config.quorum.min(panel.members.len()) {
+            resolve_dispute_internal(&env, campaign_id, resolution, majority)?;
         }
 
         Ok(())
@@ -1948,6 +2206,72 @@ fn checked_mul(a: i128, b: i128) -> Result<i128, EscrowError> {
         .ok_or(EscrowError::InvalidAmount)
 }
 
+fn read_fee_split(env: &Env) -> Vec<FeeShare> {
+    env.storage()
+        .instance()
+        .get(&DataKey::FeeSplit)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Pays out a confirmed order's platform `fee`. Without a fee split it goes
+/// to the fee collector, if one is set; with one, each beneficiary's fee
+/// balance is credited its cut and the first takes the rounding remainder.
+fn collect_fee(env: &Env, token: &Address, fee: i128) -> Result<(), EscrowError> {
+    if fee <= 0 {
+        return Ok(());
+    }
+    let split = read_fee_split(env);
+    if split.is_empty() {
+        if let Some(fee_collector) = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&DataKey::FeeCollector)
+        {
+            token::Client::new(env, token).transfer(
+                &env.current_contract_address(),
+                &fee_collector,
+                &fee,
+            );
+        }
+        return Ok(());
+    }
+
+    let mut dust = fee;
+    for share in split.iter() {
+        let amount = checked_mul(fee, share.bps as i128)? / BPS_DENOM;
+        dust = checked_sub(dust, amount)?;
+        credit_fee_balance(env, &share.beneficiary, token, amount)?;
+    }
+    if let Some(first) = split.first() {
+        credit_fee_balance(env, &first.beneficiary, token, dust)?;
+    }
+    env.events().publish(
+        (symbol_short!("fee"), symbol_short!("accrued")),
+        (token.clone(), fee),
+    );
+    Ok(())
+}
+
+fn credit_fee_balance(
+    env: &Env,
+    beneficiary: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), EscrowError> {
+    if amount <= 0 {
+        return Ok(());
+    }
+    let key = DataKey::FeeBalance(beneficiary.clone(), token.clone());
+    let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&key, &checked_add(balance, amount)?);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND);
+    Ok(())
+}
+
 fn admin(env: &Env) -> Result<Address, EscrowError> {
     env.storage()
         .instance()
@@ -2003,7 +2327,7 @@ fn load_split_order(env: &Env, order_id: u64) -> Result<SplitOrder, EscrowError>
     env.storage()
         .persistent()
         .get(&DataKey::SplitOrder(order_id))
-        .ok_or(EscrowError::SplitOrderNotFound)
+        .ok_or(EscrowError::OrderNotFound)
 }
 
 fn save_split_order(env: &Env, order_id: u64, order: &SplitOrder) {
@@ -2066,10 +2390,188 @@ fn get_buyer_order_volume(env: &Env, campaign_id: u64, buyer: &Address) -> i128
     total
 }
 
+fn arbitration_config(env: &Env) -> Result<ArbitrationConfig, EscrowError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::ArbitrationConfig)
+        .ok_or(EscrowError::ArbitrationNotConfigured)
+}
+
+fn arbitrator_pool(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Arbitrators)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn load_arbitrator_stake(env: &Env, arbitrator: &Address) -> Option<ArbitratorStake> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ArbitratorStake(arbitrator.clone()))
+}
+
+fn meets_min_stake(stake: &ArbitratorStake, config: &ArbitrationConfig) -> bool {
+    stake.token == config.stake_token && stake.amount >= config.min_stake
+}
+
+/// Persists an arbitrator's stake and keeps the pool in step with whether it
+/// still meets the configured minimum.
+fn save_arbitrator_stake(
+    env: &Env,
+    arbitrator: &Address,
+    stake: &ArbitratorStake,
+    config: &ArbitrationConfig,
+) {
+    let key = DataKey::ArbitratorStake(arbitrator.clone());
+    env.storage().persistent().set(&key, stake);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND);
+
+    let mut pool = arbitrator_pool(env);
+    match (
+        meets_min_stake(stake, config),
+        pool.first_index_of(arbitrator),
+    ) {
+        (true, None) => pool.push_back(arbitrator.clone()),
+        (false, Some(index)) => {
+            pool.remove(index);
+        }
+        _ => return,
+    }
+    env.storage().instance().set(&DataKey::Arbitrators, &pool);
+}
+
+/// Draws a panel for a campaign dispute from the staked pool using the
+/// ledger PRNG. The campaign's farmer and investors are conflicted out. No
+/// panel is drawn, leaving the dispute to the admin, if fewer eligible
+/// arbitrators remain than the quorum.
+fn draw_dispute_panel(env: &Env, campaign: &Campaign) {
+    let config = match arbitration_config(env) {
+        Ok(config) => config,
+        Err(_) => return,
+    };
+    let mut eligible: Vec<Address> = Vec::new(env);
+    for arbitrator in arbitrator_pool(env).iter() {
+        if arbitrator == campaign.farmer {
+            continue;
+        }
+        let contribution = env
+            .storage()
+            .persistent()
+            .get::<_, i128>(&DataKey::Contribution(campaign.id, arbitrator.clone()))
+            .unwrap_or(0);
+        if contribution > 0 {
+            continue;
+        }
+        if let Some(stake) = load_arbitrator_stake(env, &arbitrator) {
+            if meets_min_stake(&stake, &config) {
+                eligible.push_back(arbitrator);
+            }
+        }
+    }
+    if eligible.is_empty() || eligible.len() < config.quorum {
+        return;
+    }
+
+    env.prng().shuffle(&mut eligible);
+    let members = eligible.slice(0..config.panel_size.min(eligible.len()));
+    for arbitrator in members.iter() {
+        if let Some(mut stake) = load_arbitrator_stake(env, &arbitrator) {
+            stake.open_panels += 1;
+            save_arbitrator_stake(env, &arbitrator, &stake, &config);
+        }
+    }
+
+    let key = DataKey::DisputePanel(campaign.id);
+    env.storage().persistent().set(
+        &key,
+        &ArbitrationPanel {
+            members: members.clone(),
+            drawn_at: env.ledger().timestamp(),
+        },
+    );
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND);
+    env.events().publish(
+        (t_campaign(), symbol_short!("panel")),
+        (campaign.id, members),
+    );
+}
+
+/// Forfeits `ARBITRATOR_SLASH_BPS` of an arbitrator's stake, paid out like
+/// platform fees.
+fn slash_arbitrator(
+    env: &Env,
+    arbitrator: &Address,
+    stake: &mut ArbitratorStake,
+) -> Result<(), EscrowError> {
+    let penalty = checked_mul(stake.amount, ARBITRATOR_SLASH_BPS)? / BPS_DENOM;
+    if penalty <= 0 {
+        return Ok(());
+    }
+    stake.amount = checked_sub(stake.amount, penalty)?;
+    collect_fee(env, &stake.token, penalty)?;
+    env.events().publish(
+        (symbol_short!("arb"), symbol_short!("slashed")),
+        (arbitrator.clone(), penalty),
+    );
+    Ok(())
+}
+
+/// Releases a resolved dispute's panel and clears its votes so the campaign
+/// can be disputed afresh. Members who never voted within
+/// `VOTING_WINDOW_SECS` are slashed; when the panel reached a decision
+/// (`majority` non-empty), members who voted otherwise gain a dissent and are
+/// slashed on reaching `MAX_ARBITRATOR_DISSENTS`.
+fn close_dispute_panel(
+    env: &Env,
+    campaign_id: u64,
+    majority: &Vec<Address>,
+) -> Result<(), EscrowError> {
+    let key = DataKey::DisputePanel(campaign_id);
+    let panel: ArbitrationPanel = match env.storage().persistent().get(&key) {
+        Some(panel) => panel,
+        None => return Ok(()),
+    };
+    env.storage().persistent().remove(&key);
+    let config = arbitration_config(env)?;
+    let window_lapsed = env.ledger().timestamp() > panel.drawn_at + VOTING_WINDOW_SECS;
+
+    for arbitrator in panel.members.iter() {
+        let vote_key = DataKey::ArbitratorVote(campaign_id, arbitrator.clone());
+        let voted = env.storage().persistent().has(&vote_key);
+        env.storage().persistent().remove(&vote_key);
+
+        let mut stake = match load_arbitrator_stake(env, &arbitrator) {
+            Some(stake) => stake,
+            None => continue,
+        };
+        stake.open_panels = stake.open_panels.saturating_sub(1);
+        if !voted {
+            if window_lapsed {
+                slash_arbitrator(env, &arbitrator, &mut stake)?;
+            }
+        } else if !majority.is_empty() && !majority.contains(&arbitrator) {
+            stake.dissents += 1;
+            if stake.dissents >= MAX_ARBITRATOR_DISSENTS {
+                stake.dissents = 0;
+                slash_arbitrator(env, &arbitrator, &mut stake)?;
+            }
+        }
+        save_arbitrator_stake(env, &arbitrator, &stake, &config);
+    }
+    Ok(())
+}
+
+/// Applies a dispute resolution. `majority` lists the panel members whose
+/// vote matched it (empty for admin resolution).
 fn resolve_dispute_internal(
     env: &Env,
     campaign_id: u64,
     resolution: DisputeResolution,
+    majority: Vec<Address>,
 ) -> Result<(), EscrowError> {
     let mut campaign = load_campaign(env, campaign_id)?;
 
@@ -2090,7 +2592,7 @@ fn resolve_dispute_internal(
         }
         DisputeResolution::Partial(farmer_bps) => {
             if farmer_bps > BPS_DENOM as u32 {
-                return Err(EscrowError::InvalidResolution);
+                return Err(EscrowError::InvalidSplitRatio);
             }
             let pool = checked_add(
                 campaign.total_raised,
@@ -2116,31 +2618,9 @@ fn resolve_dispute_internal(
             );
         }
     }
-    Ok(())
-}
-
-fn has_confirmed_order(env: &Env, campaign_id: u64, buyer: &Address) -> bool {
-    // Scan orders by checking up to order_count for confirmed orders by this buyer.
-    let order_count: u64 = env
-        .storage()
-        .instance()
-        .get(&DataKey::OrderCount)
-        .unwrap_or(0);
-    for i in 1..=order_count {
-        if let Some(order) = env
-            .storage()
-            .persistent()
-            .get::<_, Order>(&DataKey::Order(i))
-        {
-            if order.campaign_id == campaign_id && order.buyer == *buyer && order.status == OrderStatus::Confirmed {
-                return true;
-            }
-        }
-    }
-    false
+    close_dispute_panel(env, campaign_id, &majority)
 }
 
-
 fn release_tranche_internal(
     env: &Env,
     campaign: &mut Campaign,
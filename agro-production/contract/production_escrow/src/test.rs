@@ -10,9 +10,9 @@ use soroban_sdk::{
 };
 
 use crate::{
-    CampaignStatus, DisputeResolution, EscrowError, OrderStatus, ProductionEscrowContract,
+    CampaignStatus, DisputeResolution, EscrowError, FeeShare, OrderStatus, ProductionEscrowContract,
     ProductionEscrowContractClient, SplitOrderResolution, SplitOrderStatus, CANCEL_WINDOW_SECS,
-    ORDER_EXPIRY_SECS,
+    ORDER_EXPIRY_SECS, VOTING_WINDOW_SECS,
 };
 
 // ---------------------------------------------------------------------------
@@ -69,7 +69,6 @@ fn setup_with_fee(fee_bps: u32) -> TestEnv<'static> {
     client.set_attester(&admin, &attester);
 
     // Leak lifetimes to 'static for convenience struct.
-    let env: Env = unsafe { std::mem::transmute(env) };
     let client: ProductionEscrowContractClient<'static> = unsafe { std::mem::transmute(client) };
 
     TestEnv {
@@ -1139,7 +1138,7 @@ fn test_partial_resolution_bps_exceeds_10000_rejected() {
         .try_resolve_dispute(&t.admin, &id, &DisputeResolution::Partial(11_000))
         .unwrap_err()
         .unwrap();
-    assert_eq!(err, EscrowError::InvalidResolution);
+    assert_eq!(err, EscrowError::InvalidSplitRatio);
 }
 
 // ===========================================================================
@@ -2225,7 +2224,7 @@ fn test_error_invalid_resolution_bps_too_high() {
         .try_resolve_dispute(&t.admin, &id, &DisputeResolution::Partial(10_001))
         .unwrap_err()
         .unwrap();
-    assert_eq!(err, EscrowError::InvalidResolution);
+    assert_eq!(err, EscrowError::InvalidSplitRatio);
 }
 
 // ---------------------------------------------------------------------------
@@ -2717,17 +2716,16 @@ fn test_mark_campaign_failed_from_funded_state_full_refund() {
     let id = t
         .client
         .create_campaign(&t.farmer, &t.token_id, &10_000, &deadline);
-    t.client.invest(&t.investor1, &id, &10_000);
-    t.client.start_production(&t.farmer, &id);
-    t.client.mark_harvest(&t.farmer, &t.attester, &id);
+    t.client.invest(&t.investor1, &id, &10_000); // → Funded
 
-    // Mark campaign as failed after harvest
+    // Mark campaign as failed before any tranche is released
     t.client.mark_campaign_failed(&t.admin, &id);
     assert_eq!(t.client.get_campaign(&id).status, CampaignStatus::Failed);
 
     // All investors should be refunded their full investment
-    let investor1_balance_after = t.token_contract.balance(&t.investor1);
-    assert_eq!(investor1_balance_after, 10_000 + 10_000); // initial + refund
+    let before = balance(&t, &t.investor1);
+    assert_eq!(t.client.refund(&t.investor1, &id), 10_000);
+    assert_eq!(balance(&t, &t.investor1), before + 10_000);
 }
 
 #[test]
@@ -3706,3 +3704,316 @@ fn test_fund_split_order_non_co_buyer_fails() {
     let result = t.client.try_fund_split_order(&stranger, &order_id);
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotCoBuyer);
 }
+
+#[test]
+fn test_transfer_split_share_moves_the_slot() {
+    let (t, campaign_id, co_buyers) = setup_split_ready(2);
+    let (first, second) = (co_buyers.get(0).unwrap(), co_buyers.get(1).unwrap());
+    let mut shares = Vec::new(&t.env);
+    shares.push_back(600i128);
+    shares.push_back(400i128);
+    let order_id = t
+        .client
+        .create_split_order(&first, &campaign_id, &co_buyers, &shares);
+    t.client.fund_split_order(&first, &order_id);
+
+    let newcomer = Address::generate(&t.env);
+    let result = t.client.try_transfer_split_share(&first, &t.farmer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotBuyer);
+    let result = t.client.try_transfer_split_share(&first, &second, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyContributed);
+    let result = t.client.try_transfer_split_share(&newcomer, &first, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotCoBuyer);
+
+    t.client.transfer_split_share(&first, &newcomer, &order_id);
+    let order = t.client.get_split_order(&order_id);
+    assert_eq!(order.co_buyers, Vec::from_array(&t.env, [newcomer.clone(), second.clone()]));
+    assert_eq!(order.shares.get(newcomer.clone()), Some(600));
+    assert_eq!(order.funded.get(newcomer.clone()), Some(true));
+    assert!(!order.shares.contains_key(first.clone()));
+
+    // The newcomer's funding counts: the last share activates the order.
+    t.client.fund_split_order(&second, &order_id);
+    assert_eq!(
+        t.client.get_split_order(&order_id).status,
+        SplitOrderStatus::Active
+    );
+    t.client.confirm_split_receipt(&newcomer, &order_id);
+    assert_eq!(
+        t.client.get_split_order(&order_id).status,
+        SplitOrderStatus::Confirmed
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Fee split
+// ---------------------------------------------------------------------------
+
+fn fee_share(beneficiary: &Address, bps: u32) -> FeeShare {
+    FeeShare {
+        beneficiary: beneficiary.clone(),
+        bps,
+    }
+}
+
+#[test]
+fn test_set_fee_split_rejects_invalid_ratios() {
+    let t = setup();
+    let treasury = Address::generate(&t.env);
+    let insurance = Address::generate(&t.env);
+
+    let short = Vec::from_array(
+        &t.env,
+        [fee_share(&treasury, 7_000), fee_share(&insurance, 2_000)],
+    );
+    let err = t
+        .client
+        .try_set_fee_split(&t.admin, &short)
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, EscrowError::InvalidSplitRatio);
+
+    let duplicate = Vec::from_array(
+        &t.env,
+        [fee_share(&treasury, 5_000), fee_share(&treasury, 5_000)],
+    );
+    let err = t
+        .client
+        .try_set_fee_split(&t.admin, &duplicate)
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, EscrowError::InvalidSplitRatio);
+
+    let zero = Vec::from_array(
+        &t.env,
+        [fee_share(&treasury, 10_000), fee_share(&insurance, 0)],
+    );
+    let err = t
+        .client
+        .try_set_fee_split(&t.admin, &zero)
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, EscrowError::InvalidSplitRatio);
+    assert_eq!(t.client.get_fee_split().len(), 0);
+}
+
+#[test]
+fn test_fee_split_accrues_and_claims_order_fees() {
+    let t = setup();
+    let treasury = Address::generate(&t.env);
+    let insurance = Address::generate(&t.env);
+    let deadline = future_deadline(&t);
+    let id = t
+        .client
+        .create_campaign(&t.farmer, &t.token_id, &10_000, &deadline);
+    t.client.invest(&t.investor1, &id, &10_000);
+    t.client.start_production(&t.farmer, &id);
+    t.client.mark_harvest(&t.farmer, &t.attester, &id);
+
+    let split = Vec::from_array(
+        &t.env,
+        [fee_share(&treasury, 7_000), fee_share(&insurance, 3_000)],
+    );
+    t.client.set_fee_split(&t.admin, &split);
+    assert_eq!(t.client.get_fee_split(), split);
+
+    // 3% of 500 is 15, split 10.5 / 4.5; the dust goes to the treasury.
+    let order_id = t.client.create_order(&t.buyer, &id, &500);
+    t.client.confirm_order(&t.buyer, &order_id);
+    assert_eq!(balance(&t, &t.fee_collector), 0);
+    assert_eq!(t.client.get_fee_balance(&treasury, &t.token_id), 11);
+    assert_eq!(t.client.get_fee_balance(&insurance, &t.token_id), 4);
+
+    assert_eq!(t.client.claim_fees(&treasury, &t.token_id), 11);
+    assert_eq!(balance(&t, &treasury), 11);
+    assert_eq!(t.client.get_fee_balance(&treasury, &t.token_id), 0);
+    // Claiming an empty balance is a no-op rather than an error.
+    assert_eq!(t.client.claim_fees(&treasury, &t.token_id), 0);
+
+    // Clearing the split pays the fee collector directly again.
+    t.client.set_fee_split(&t.admin, &Vec::new(&t.env));
+    let order_id = t.client.create_order(&t.buyer, &id, &400);
+    t.client.confirm_order(&t.buyer, &order_id);
+    assert_eq!(balance(&t, &t.fee_collector), 12);
+}
+
+// ---------------------------------------------------------------------------
+// Staked arbitrator pool
+// ---------------------------------------------------------------------------
+
+/// Mints `count` arbitrators 1_000 each, stakes them all and configures the
+/// pool, returning them in staking order.
+fn stake_arbitrators(t: &TestEnv, count: u32, panel_size: u32, quorum: u32) -> Vec<Address> {
+    let sac = StellarAssetClient::new(&t.env, &t.token_id);
+    t.client
+        .set_arbitration_config(&t.admin, &t.token_id, &500, &panel_size, &quorum);
+    let mut arbitrators = Vec::new(&t.env);
+    for _ in 0..count {
+        let arbitrator = Address::generate(&t.env);
+        sac.mint(&arbitrator, &1_000);
+        t.client.stake_arbitrator(&arbitrator, &1_000);
+        arbitrators.push_back(arbitrator);
+    }
+    arbitrators
+}
+
+#[test]
+fn test_set_arbitration_config_rejects_invalid_parameters() {
+    let t = setup();
+    for (min_stake, panel_size, quorum) in [(0, 3, 2), (500, 10, 2), (500, 3, 0), (500, 3, 4)] {
+        let err = t
+            .client
+            .try_set_arbitration_config(&t.admin, &t.token_id, &min_stake, &panel_size, &quorum)
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, EscrowError::InvalidArbitrationConfig);
+    }
+}
+
+#[test]
+fn test_arbitrator_joins_pool_at_min_stake_and_leaves_on_unstake() {
+    let t = setup();
+    t.client
+        .set_arbitration_config(&t.admin, &t.token_id, &500, &3, &2);
+    let arbitrator = Address::generate(&t.env);
+    StellarAssetClient::new(&t.env, &t.token_id).mint(&arbitrator, &1_000);
+
+    t.client.stake_arbitrator(&arbitrator, &400);
+    assert!(t.client.get_arbitrators().is_empty());
+    t.client.stake_arbitrator(&arbitrator, &100);
+    assert_eq!(
+        t.client.get_arbitrators(),
+        Vec::from_array(&t.env, [arbitrator.clone()])
+    );
+
+    let err = t
+        .client
+        .try_unstake_arbitrator(&arbitrator, &501)
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, EscrowError::InsufficientStake);
+    t.client.unstake_arbitrator(&arbitrator, &1);
+    assert!(t.client.get_arbitrators().is_empty());
+    assert_eq!(t.client.get_arbitrator_stake(&arbitrator).amount, 499);
+    assert_eq!(balance(&t, &arbitrator), 501);
+}
+
+#[test]
+fn test_dispute_panel_excludes_campaign_farmer_and_investors() {
+    let t = setup();
+    let sac = StellarAssetClient::new(&t.env, &t.token_id);
+    let arbitrators = stake_arbitrators(&t, 2, 3, 2);
+    // The farmer and an investor stake too, but are conflicted out.
+    sac.mint(&t.farmer, &1_000);
+    t.client.stake_arbitrator(&t.farmer, &1_000);
+    t.client.stake_arbitrator(&t.investor1, &1_000);
+    assert_eq!(t.client.get_arbitrators().len(), 4);
+
+    let id = t
+        .client
+        .create_campaign(&t.farmer, &t.token_id, &10_000, &future_deadline(&t));
+    t.client.invest(&t.investor1, &id, &10_000);
+    t.client.open_dispute(&t.farmer, &id);
+
+    let panel = t.client.get_dispute_panel(&id).unwrap();
+    assert_eq!(panel.members.len(), 2);
+    for arbitrator in arbitrators.iter() {
+        assert!(panel.members.contains(&arbitrator));
+    }
+    let err = t
+        .client
+        .try_vote_to_resolve(&t.investor1, &id, &DisputeResolution::FullPayoutToInvestors)
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, EscrowError::ArbitratorNotFound);
+}
+
+#[test]
+fn test_panel_quorum_resolves_dispute_and_releases_panel() {
+    let t = setup();
+    let arbitrators = stake_arbitrators(&t, 3, 3, 2);
+    let id = t
+        .client
+        .create_campaign(&t.farmer, &t.token_id, &10_000, &future_deadline(&t));
+    t.client.invest(&t.investor1, &id, &10_000);
+    t.client.open_dispute(&t.farmer, &id);
+    for arbitrator in arbitrators.iter() {
+        assert_eq!(t.client.get_arbitrator_stake(&arbitrator).open_panels, 1);
+    }
+
+    let first = arbitrators.get(0).unwrap();
+    t.client
+        .vote_to_resolve(&first, &id, &DisputeResolution::RefundInvestors);
+    // A vote for a different outcome doesn't count towards the first one.
+    t.client.vote_to_resolve(
+        &arbitrators.get(1).unwrap(),
+        &id,
+        &DisputeResolution::Partial(5_000),
+    );
+    assert_eq!(t.client.get_campaign(&id).status, CampaignStatus::Disputed);
+    let err = t
+        .client
+        .try_vote_to_resolve(&first, &id, &DisputeResolution::RefundInvestors)
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, EscrowError::AlreadyVoted);
+
+    t.client.vote_to_resolve(
+        &arbitrators.get(2).unwrap(),
+        &id,
+        &DisputeResolution::RefundInvestors,
+    );
+    assert_eq!(t.client.get_campaign(&id).status, CampaignStatus::Failed);
+    assert_eq!(t.client.get_dispute_panel(&id), None);
+    for arbitrator in arbitrators.iter() {
+        let stake = t.client.get_arbitrator_stake(&arbitrator);
+        assert_eq!(stake.open_panels, 0);
+        assert_eq!(stake.amount, 1_000);
+    }
+    // The dissenting arbitrator is marked but not yet slashed.
+    let dissenter = arbitrators.get(1).unwrap();
+    assert_eq!(t.client.get_arbitrator_stake(&dissenter).dissents, 1);
+}
+
+#[test]
+fn test_panel_members_who_miss_the_voting_window_are_slashed() {
+    let t = setup();
+    let arbitrators = stake_arbitrators(&t, 3, 3, 2);
+    let id = t
+        .client
+        .create_campaign(&t.farmer, &t.token_id, &10_000, &future_deadline(&t));
+    t.client.invest(&t.investor1, &id, &10_000);
+    t.client.open_dispute(&t.farmer, &id);
+    let voter = arbitrators.get(0).unwrap();
+    t.client
+        .vote_to_resolve(&voter, &id, &DisputeResolution::RefundInvestors);
+
+    advance_ledger(&t.env, VOTING_WINDOW_SECS + 1);
+    t.client
+        .resolve_dispute(&t.admin, &id, &DisputeResolution::RefundInvestors);
+
+    // Each silent member forfeits 10% of their 1_000 stake to the collector.
+    assert_eq!(t.client.get_arbitrator_stake(&voter).amount, 1_000);
+    for arbitrator in [arbitrators.get(1).unwrap(), arbitrators.get(2).unwrap()] {
+        assert_eq!(t.client.get_arbitrator_stake(&arbitrator).amount, 900);
+    }
+    assert_eq!(balance(&t, &t.fee_collector), 200);
+    // 900 still meets the 500 minimum, so nobody leaves the pool.
+    assert_eq!(t.client.get_arbitrators().len(), 3);
+}
+
+#[test]
+fn test_dispute_without_enough_eligible_arbitrators_falls_back_to_admin() {
+    let t = setup();
+    stake_arbitrators(&t, 1, 3, 2);
+    let id = t
+        .client
+        .create_campaign(&t.farmer, &t.token_id, &10_000, &future_deadline(&t));
+    t.client.invest(&t.investor1, &id, &10_000);
+    t.client.open_dispute(&t.farmer, &id);
+    assert_eq!(t.client.get_dispute_panel(&id), None);
+
+    t.client
+        .resolve_dispute(&t.admin, &id, &DisputeResolution::RefundInvestors);
+    assert_eq!(t.client.get_campaign(&id).status, CampaignStatus::Failed);
+}
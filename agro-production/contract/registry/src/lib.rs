@@ -74,18 +74,6 @@ pub struct BatchRecord {
     pub linked_order_ids: Vec<u64>,
 }
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct BatchRecord {
-    pub batch_id: u64,
-    pub campaign_id: u64,
-    pub farmer: Address,
-    pub crop: String,
-    pub harvest_date: u64,
-    pub quantity: i128,
-    pub linked_order_ids: Vec<u64>,
-}
-
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
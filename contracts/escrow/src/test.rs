@@ -1,4 +1,5 @@
 #![cfg(test)]
+#![allow(clippy::bool_assert_comparison)]
 
 use super::*;
 use soroban_sdk::{
@@ -6,6 +7,25 @@ use soroban_sdk::{
     token, Address, Env, IntoVal, String,
 };
 
+/// soroban-sdk 22 has no `filter_by_contract` on the recorded event list;
+/// this provides it for the pinned SDK with the same semantics as later
+/// releases.
+trait FilterByContract {
+    fn filter_by_contract(&self, contract: &Address) -> Self;
+}
+
+impl FilterByContract for Vec<(Address, Vec<soroban_sdk::Val>, soroban_sdk::Val)> {
+    fn filter_by_contract(&self, contract: &Address) -> Self {
+        let mut events = Vec::new(self.env());
+        for event in self.iter() {
+            if event.0 == *contract {
+                events.push_back(event);
+            }
+        }
+        events
+    }
+}
+
 fn setup_test() -> (
     Env,
     EscrowContractClient<'static>,
@@ -128,7 +148,8 @@ fn test_create_and_confirm_order() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     assert_eq!(order_id, 1);
 
@@ -148,7 +169,8 @@ fn test_mark_delivered_then_confirm() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     env.ledger().set_timestamp(1000);
     client.mock_all_auths().mark_delivered(&farmer, &admin, &order_id);
@@ -170,7 +192,7 @@ fn test_mark_delivered_wrong_farmer_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     let result = client
         .mock_all_auths()
@@ -184,7 +206,8 @@ fn test_mark_delivered_twice_succeeds() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     client.mock_all_auths().mark_delivered(&farmer, &admin, &order_id);
     env.ledger().set_timestamp(1000);
@@ -202,7 +225,7 @@ fn test_mark_delivered_wrong_attester_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     let result = client
         .mock_all_auths()
@@ -216,7 +239,8 @@ fn test_mark_delivered_falls_back_to_admin_before_attester_configured() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     // No set_attester call: admin is the fallback attester.
     client.mock_all_auths().mark_delivered(&farmer, &admin, &order_id);
@@ -232,7 +256,8 @@ fn test_mark_delivered_uses_configured_attester() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     // Admin (the pre-attester fallback) can no longer co-sign once a
     // dedicated attester is configured.
@@ -254,7 +279,8 @@ fn test_confirm_without_mark_delivered() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     client.mock_all_auths().confirm_receipt(&buyer, &order_id);
 
@@ -267,7 +293,8 @@ fn test_confirm_already_completed() {
     let (_env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     client.mock_all_auths().confirm_receipt(&buyer, &order_id);
 
@@ -286,12 +313,12 @@ fn test_refund_expired_order() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
 
     assert_eq!(token.balance(&buyer), 1000 - amount);
-    assert_eq!(token.balance(&collector), fee);
+    assert_eq!(token.balance(&collector), 0);
     assert_eq!(token.balance(&farmer), 0);
-    assert_eq!(token.balance(&contract_id), net_amount);
+    assert_eq!(token.balance(&contract_id), net_amount + fee);
 
     env.ledger()
         .set_timestamp(env.ledger().timestamp() + 345_601);
@@ -301,8 +328,9 @@ fn test_refund_expired_order() {
     let order = client.get_order_details(&order_id);
     assert_eq!(order.status, OrderStatus::Refunded);
 
-    assert_eq!(token.balance(&buyer), 1000 - amount + net_amount);
-    assert_eq!(token.balance(&collector), fee);
+    // The escrowed fee is refunded along with the net amount.
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(token.balance(&collector), 0);
     assert_eq!(token.balance(&farmer), 0);
     assert_eq!(token.balance(&contract_id), 0);
 }
@@ -312,7 +340,7 @@ fn test_refund_unexpired_order_fails() {
     let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     env.ledger().set_timestamp(env.ledger().timestamp() + 3600);
 
@@ -329,16 +357,19 @@ fn test_cancel_order_within_window_refunds_buyer() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
 
     env.ledger().set_timestamp(env.ledger().timestamp() + 60);
     client.mock_all_auths().cancel_order(&buyer, &order_id);
 
     let order = client.get_order_details(&order_id);
     assert_eq!(order.status, OrderStatus::Refunded);
+    assert_eq!(order.amount + order.fee, amount);
+    assert_eq!(order.fee, fee);
+    assert_eq!(order.amount, net_amount);
 
-    assert_eq!(token.balance(&buyer), 1000 - amount + net_amount);
-    assert_eq!(token.balance(&collector), fee);
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(token.balance(&collector), 0);
     assert_eq!(token.balance(&contract_id), 0);
 }
 
@@ -347,13 +378,13 @@ fn test_cancel_order_fails_after_window_closes() {
     let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     env.ledger()
         .set_timestamp(env.ledger().timestamp() + CANCEL_WINDOW_SECONDS + 1);
 
     let result = client.mock_all_auths().try_cancel_order(&buyer, &order_id);
-    assert_eq!(result.unwrap_err().unwrap(), EscrowError::CancelWindowClosed);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowClosed);
 }
 
 #[test]
@@ -361,7 +392,8 @@ fn test_cancel_order_fails_after_delivery() {
     let (_env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     client
         .mock_all_auths()
@@ -377,7 +409,7 @@ fn test_cancel_order_wrong_buyer_fails() {
     let stranger = Address::generate(&env);
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     let result = client.mock_all_auths().try_cancel_order(&stranger, &order_id);
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotBuyer);
@@ -389,7 +421,7 @@ fn test_cancel_order_emits_distinct_event() {
     let amount = 500i128;
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
 
     client.mock_all_auths().cancel_order(&buyer, &order_id);
 
@@ -422,6 +454,8 @@ fn test_create_order_unsupported_token_fails() {
         &farmer,
         &unsupported_client.address,
         &500,
+        &None,
+        &None,
     );
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::UnsupportedToken);
 }
@@ -432,7 +466,7 @@ fn test_create_order_zero_amount_fails() {
 
     let result = client
         .mock_all_auths()
-        .try_create_order(&buyer, &farmer, &token.address, &0);
+        .try_create_order(&buyer, &farmer, &token.address, &0, &None, &None);
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountMustBePositive);
 }
 
@@ -442,7 +476,7 @@ fn test_create_order_negative_amount_fails() {
 
     let result = client
         .mock_all_auths()
-        .try_create_order(&buyer, &farmer, &token.address, &-1);
+        .try_create_order(&buyer, &farmer, &token.address, &-1, &None, &None);
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountMustBePositive);
 }
 
@@ -451,7 +485,7 @@ fn test_create_order_buyer_auth_fails() {
     let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
 
     // Use mock_all_auths to successfully create an order while recording auths.
-    client.mock_all_auths().create_order(&buyer, &farmer, &token.address, &500);
+    client.mock_all_auths().create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     // Verify that the buyer's authorization was required by the contract.
     let auths = env.auths();
@@ -467,14 +501,18 @@ fn test_platform_fee_acceptance_criteria() {
 
     client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &1);
 
-    assert_eq!(token.balance(&collector), 30);
+    // The fee is escrowed with the order and only forwarded on confirmation.
+    assert_eq!(token.balance(&collector), 0);
     let order_details = client.get_order_details(&1);
     assert_eq!(order_details.amount, 970);
+    assert_eq!(order_details.fee, 30);
 
     client.mock_all_auths().confirm_receipt(&buyer, &1);
     assert_eq!(token.balance(&farmer), 970);
+    assert_eq!(token.balance(&collector), 30);
 }
 
 #[test]
@@ -483,7 +521,8 @@ fn test_open_dispute_by_buyer() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     let reason = String::from_str(&_env, "Product damaged");
     let evidence_hash = String::from_str(&_env, "QmHash123");
@@ -506,7 +545,8 @@ fn test_open_dispute_by_farmer() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     let reason = String::from_str(&_env, "Buyer not responding");
     let evidence_hash = String::from_str(&_env, "QmHash456");
@@ -529,7 +569,8 @@ fn test_open_dispute_not_pending_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     client.mock_all_auths().confirm_receipt(&buyer, &order_id);
 
@@ -551,7 +592,8 @@ fn test_open_dispute_not_participant_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     let reason = String::from_str(&env, "Not involved");
     let evidence_hash = String::from_str(&env, "QmHashXYZ");
@@ -575,7 +617,8 @@ fn test_open_dispute_duplicate_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     let reason = String::from_str(&_env, "First dispute");
     let evidence_hash = String::from_str(&_env, "QmHash111");
@@ -607,11 +650,12 @@ fn test_resolve_dispute_refund() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     assert_eq!(token.balance(&buyer), 1000 - amount);
-    assert_eq!(token.balance(&collector), fee);
-    assert_eq!(token.balance(&contract_id), net_amount);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), net_amount + fee);
     assert_eq!(token.balance(&farmer), 0);
 
     let reason = String::from_str(&_env, "Product not received");
@@ -624,13 +668,14 @@ fn test_resolve_dispute_refund() {
     client
         .mock_all_auths()
         .resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    finalize_ruling(&_env, &client, order_id);
 
     let order = client.get_order_details(&order_id);
     assert_eq!(order.status, OrderStatus::Refunded);
     assert_eq!(order.amount, net_amount);
 
-    assert_eq!(token.balance(&buyer), 1000 - amount + net_amount);
-    assert_eq!(token.balance(&collector), fee);
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(token.balance(&collector), 0);
     assert_eq!(token.balance(&contract_id), 0);
     assert_eq!(token.balance(&farmer), 0);
 }
@@ -644,11 +689,12 @@ fn test_resolve_dispute_release() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     assert_eq!(token.balance(&buyer), 1000 - amount);
-    assert_eq!(token.balance(&collector), fee);
-    assert_eq!(token.balance(&contract_id), net_amount);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), net_amount + fee);
     assert_eq!(token.balance(&farmer), 0);
 
     let reason = String::from_str(&_env, "Farmer delivered goods");
@@ -661,6 +707,7 @@ fn test_resolve_dispute_release() {
     client
         .mock_all_auths()
         .resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    finalize_ruling(&_env, &client, order_id);
 
     let order = client.get_order_details(&order_id);
     assert_eq!(order.status, OrderStatus::Completed);
@@ -681,14 +728,16 @@ fn test_resolve_dispute_split_50_50() {
     let buyer_share_bps: u32 = 5000;
     let refund_amount = net_amount * buyer_share_bps as i128 / 10_000;
     let release_amount = net_amount - refund_amount;
+    let refund_fee = fee * buyer_share_bps as i128 / 10_000;
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &amount);
+        .create_order(&buyer, &farmer, &token.address, &amount, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     assert_eq!(token.balance(&buyer), 1000 - amount);
-    assert_eq!(token.balance(&collector), fee);
-    assert_eq!(token.balance(&contract_id), net_amount);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), net_amount + fee);
     assert_eq!(token.balance(&farmer), 0);
 
     let reason = String::from_str(&_env, "Partial fulfillment");
@@ -701,13 +750,14 @@ fn test_resolve_dispute_split_50_50() {
     client
         .mock_all_auths()
         .resolve_dispute(&admin, &order_id, &DisputeResolution::Split(buyer_share_bps));
+    finalize_ruling(&_env, &client, order_id);
 
     let order = client.get_order_details(&order_id);
     assert_eq!(order.status, OrderStatus::Completed);
     assert_eq!(order.amount, net_amount);
 
-    assert_eq!(token.balance(&buyer), 1000 - amount + refund_amount);
-    assert_eq!(token.balance(&collector), fee);
+    assert_eq!(token.balance(&buyer), 1000 - amount + refund_amount + refund_fee);
+    assert_eq!(token.balance(&collector), fee - refund_fee);
     assert_eq!(token.balance(&contract_id), 0);
     assert_eq!(token.balance(&farmer), release_amount);
 }
@@ -718,7 +768,8 @@ fn test_resolve_dispute_not_admin_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     let reason = String::from_str(&env, "Dispute");
     let evidence_hash = String::from_str(&env, "QmHashNotAdmin");
@@ -743,7 +794,7 @@ fn test_resolve_dispute_not_disputed_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     let result =
         client
@@ -759,7 +810,8 @@ fn test_resolve_dispute_invalid_split_ratio_fails() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
 
     let reason = String::from_str(&_env, "Dispute");
     let evidence_hash = String::from_str(&_env, "QmHashInvalidRatio");
@@ -783,13 +835,13 @@ fn test_get_orders_by_buyer() {
 
     let _order_id1 = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
     let _order_id2 = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &300);
+        .create_order(&buyer, &farmer, &token.address, &300, &None, &None);
 
-    let orders = client.get_orders_by_buyer(&buyer);
+    let orders = client.get_orders_by_buyer(&buyer, &0, &10, &None);
     assert_eq!(orders.len(), 2);
 }
 
@@ -799,12 +851,112 @@ fn test_get_orders_by_farmer() {
 
     let _order_id1 = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
 
-    let orders = client.get_orders_by_farmer(&farmer);
+    let orders = client.get_orders_by_farmer(&farmer, &0, &10, &None);
     assert_eq!(orders.len(), 1);
 }
 
+#[test]
+fn test_order_indices_paginate_and_filter_by_status() {
+    let (_env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let mut order_ids = Vec::new(&_env);
+    for _ in 0..5 {
+        order_ids.push_back(client.create_order(&buyer, &farmer, &token.address, &100, &None, &None));
+    }
+    client.reject_order(&farmer, &order_ids.get(1).unwrap());
+    client.reject_order(&farmer, &order_ids.get(3).unwrap());
+
+    assert_eq!(client.get_buyer_order_count(&buyer), 5);
+    assert_eq!(client.get_farmer_order_count(&farmer), 5);
+    assert_eq!(
+        client.get_orders_by_buyer(&buyer, &0, &2, &None),
+        order_ids.slice(0..2)
+    );
+    assert_eq!(
+        client.get_orders_by_farmer(&farmer, &4, &2, &None),
+        order_ids.slice(4..5)
+    );
+    assert_eq!(client.get_orders_by_buyer(&buyer, &9, &2, &None).len(), 0);
+
+    // The status filter applies within the scanned window.
+    let refunded = client.get_orders_by_buyer(&buyer, &0, &3, &Some(OrderStatus::Refunded));
+    assert_eq!(refunded, Vec::from_array(&_env, [order_ids.get(1).unwrap()]));
+    let awaiting = client.get_orders_by_farmer(&farmer, &0, &5, &Some(OrderStatus::Awaiting));
+    assert_eq!(awaiting.len(), 3);
+}
+
+#[test]
+fn test_migrate_order_indices_keeps_legacy_orders_first() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+
+    // Pre-upgrade index entries for orders 1 and 2, then an order indexed
+    // after the upgrade.
+    env.as_contract(&contract_id, || {
+        let legacy = Vec::from_array(&env, [1u64, 2u64]);
+        env.storage().persistent().set(&DataKey::BuyerOrders(buyer.clone()), &legacy);
+        env.storage().persistent().set(&DataKey::FarmerOrders(farmer.clone()), &legacy);
+        env.storage().instance().set(&DataKey::OrderCount, &2u64);
+    });
+    let current = client.create_order(&buyer, &farmer, &token.address, &100, &None, &None);
+    let expected = Vec::from_array(&env, [1u64, 2u64, current]);
+    assert_eq!(client.get_buyer_order_count(&buyer), 3);
+    assert_eq!(client.get_orders_by_buyer(&buyer, &0, &10, &None), expected);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client
+            .try_migrate_order_indices(&stranger, &Vec::from_array(&env, [buyer.clone()]))
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::NotAdmin
+    );
+    let parties = Vec::from_array(&env, [buyer.clone(), farmer.clone(), stranger]);
+    assert_eq!(client.migrate_order_indices(&admin, &parties), 4);
+    assert_eq!(client.migrate_order_indices(&admin, &parties), 0);
+
+    assert_eq!(client.get_buyer_order_count(&buyer), 3);
+    assert_eq!(client.get_orders_by_buyer(&buyer, &0, &10, &None), expected);
+    assert_eq!(client.get_orders_by_farmer(&farmer, &0, &10, &None), expected);
+}
+
+#[test]
+fn test_migrate_order_indices_resumes_in_chunks() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+
+    // A pre-upgrade buyer index longer than one migration chunk.
+    let legacy = Vec::from_array(&env, core::array::from_fn::<u64, 120, _>(|i| i as u64 + 1));
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&DataKey::BuyerOrders(buyer.clone()), &legacy);
+        env.storage().instance().set(&DataKey::OrderCount, &120u64);
+    });
+    let current = client.create_order(&buyer, &farmer, &token.address, &100, &None, &None);
+    let mut expected = legacy.clone();
+    expected.push_back(current);
+
+    let parties = Vec::from_array(&env, [buyer.clone()]);
+    let page = |start: u64| client.get_orders_by_buyer(&buyer, &start, &50, &None);
+    for moved in [50, 50, 20, 0] {
+        assert_eq!(client.get_buyer_order_count(&buyer), 121);
+        assert_eq!(page(40), expected.slice(40..90));
+        assert_eq!(page(100), expected.slice(100..121));
+        assert_eq!(client.migrate_order_indices(&admin, &parties), moved);
+    }
+    assert_eq!(client.get_buyer_order_count(&buyer), 121);
+    assert_eq!(page(40), expected.slice(40..90));
+    assert_eq!(page(100), expected.slice(100..121));
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&DataKey::BuyerOrders(buyer.clone())));
+    });
+
+    let next = client.create_order(&buyer, &farmer, &token.address, &100, &None, &None);
+    assert_eq!(
+        client.get_orders_by_buyer(&buyer, &120, &5, &None),
+        Vec::from_array(&env, [current, next])
+    );
+}
+
 #[test]
 fn test_initialize_with_only_one_token_fails() {
     let env = Env::default();
@@ -965,7 +1117,7 @@ fn test_create_order_uses_configured_fee_rate() {
         setup_test();
 
     client.set_fee_config(&admin, &fee_collector, &1_000); // 10%
-    let order_id = client.create_order(&buyer, &farmer, &xlm.address, &1_000);
+    let order_id = client.create_order(&buyer, &farmer, &xlm.address, &1_000, &None, &None);
     let order = client.get_order_details(&order_id);
     // 10% fee -> net amount is 900.
     assert_eq!(order.amount, 900);
@@ -997,7 +1149,25 @@ impl MockRouter {
         env.storage().instance().set(&symbol_short!("report"), &reported);
     }
 
-    pub fn get_quote(env: Env, _send_token: Address, _dest_token: Address, _send_amount: i128) -> i128 {
+    pub fn get_quote(
+        env: Env,
+        _send_token: Address,
+        _dest_token: Address,
+        _path: Vec<Address>,
+        _send_amount: i128,
+    ) -> i128 {
+        env.storage().instance().get(&symbol_short!("quote")).unwrap()
+    }
+
+    /// For strict-receive, `quote` is the quoted input and `actual_out` the
+    /// input the swap really spends.
+    pub fn get_quote_out(
+        env: Env,
+        _send_token: Address,
+        _dest_token: Address,
+        _path: Vec<Address>,
+        _dest_amount: i128,
+    ) -> i128 {
         env.storage().instance().get(&symbol_short!("quote")).unwrap()
     }
 
@@ -1007,17 +1177,41 @@ impl MockRouter {
         to: Address,
         send_token: Address,
         dest_token: Address,
+        path: Vec<Address>,
         send_amount: i128,
         _dest_min: i128,
     ) -> i128 {
         let actual_out: i128 = env.storage().instance().get(&symbol_short!("actual")).unwrap();
         token::Client::new(&env, &send_token).transfer(&from, &env.current_contract_address(), &send_amount);
         token::Client::new(&env, &dest_token).transfer(&env.current_contract_address(), &to, &actual_out);
+        env.storage().instance().set(&symbol_short!("path"), &path);
         env.storage()
             .instance()
             .get(&symbol_short!("report"))
             .unwrap_or(actual_out)
     }
+
+    pub fn swap_exact_out(
+        env: Env,
+        from: Address,
+        to: Address,
+        send_token: Address,
+        dest_token: Address,
+        path: Vec<Address>,
+        dest_amount: i128,
+        _max_send: i128,
+    ) -> i128 {
+        let actual_in: i128 = env.storage().instance().get(&symbol_short!("actual")).unwrap();
+        let router = env.current_contract_address();
+        token::Client::new(&env, &send_token).transfer_from(&router, &from, &router, &actual_in);
+        token::Client::new(&env, &dest_token).transfer(&router, &to, &dest_amount);
+        env.storage().instance().set(&symbol_short!("path"), &path);
+        actual_in
+    }
+
+    pub fn last_path(env: Env) -> Vec<Address> {
+        env.storage().instance().get(&symbol_short!("path")).unwrap()
+    }
 }
 
 fn setup_path_payment_test(
@@ -1084,7 +1278,7 @@ fn setup_path_payment_test(
 fn test_create_order_via_path_payment_success() {
     // Router quotes 1000 and actually delivers 990 (1% slippage), within the
     // default 1% (100 bps) tolerance.
-    let (_env, client, buyer, farmer, fee_collector, source_token, settlement_token, _admin) =
+    let (env, client, buyer, farmer, fee_collector, source_token, settlement_token, _admin) =
         setup_path_payment_test(1_000, 990);
 
     let order_id = client.create_order_via_path_payment(
@@ -1094,14 +1288,18 @@ fn test_create_order_via_path_payment_success() {
         &1_000,
         &settlement_token.address,
         &900, // buyer's own loose floor; contract's tolerance floor (990) governs
+        &Vec::new(&env),
+        &None,
     );
+    client.accept_order(&farmer, &order_id);
 
     let order = client.get_order_details(&order_id);
     assert_eq!(order.status, OrderStatus::Pending);
     assert_eq!(order.token, settlement_token.address);
     // Default 3% platform fee on the 990 actually received.
     assert_eq!(order.amount, 990 - (990 * 3 / 100));
-    assert_eq!(settlement_token.balance(&fee_collector), 990 * 3 / 100);
+    assert_eq!(order.fee, 990 * 3 / 100);
+    assert_eq!(settlement_token.balance(&fee_collector), 0);
     assert_eq!(source_token.balance(&buyer), 10_000 - 1_000);
 }
 
@@ -1110,7 +1308,7 @@ fn test_create_order_via_path_payment_slippage_rejected() {
     // Router quotes 1000 but only delivers 900 (10% slippage), exceeding the
     // default 1% (100 bps) tolerance, so the order must be rejected and no funds
     // should move.
-    let (_env, client, buyer, farmer, _fee_collector, source_token, settlement_token, _admin) =
+    let (env, client, buyer, farmer, _fee_collector, source_token, settlement_token, _admin) =
         setup_path_payment_test(1_000, 900);
 
     let result = client.try_create_order_via_path_payment(
@@ -1120,6 +1318,8 @@ fn test_create_order_via_path_payment_slippage_rejected() {
         &1_000,
         &settlement_token.address,
         &1, // buyer floor is not the binding constraint here
+        &Vec::new(&env),
+        &None,
     );
 
     assert_eq!(
@@ -1144,6 +1344,8 @@ fn test_create_order_via_path_payment_unsupported_settlement_token_fails() {
         &1_000,
         &other_client.address(),
         &900,
+        &Vec::new(&_env),
+        &None,
     );
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::UnsupportedToken);
     let _ = admin; // admin unused beyond setup in this test
@@ -1183,6 +1385,8 @@ fn test_create_order_via_path_payment_no_router_configured_fails() {
         &1_000,
         &settlement_contract.address(),
         &900,
+        &Vec::new(&env),
+        &None,
     );
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::RouterNotConfigured);
 }
@@ -1232,6 +1436,8 @@ fn test_create_order_via_path_payment_rejects_router_misreporting_dest_received(
         &1_000,
         &settlement_contract.address(),
         &1, // buyer's own floor is not the binding constraint here
+        &Vec::new(&env),
+        &None,
     );
 
     // Rejected because the escrow's measured settlement-token balance only
@@ -1244,6 +1450,362 @@ fn test_create_order_via_path_payment_rejects_router_misreporting_dest_received(
     assert_eq!(source_client.balance(&buyer), 10_000);
 }
 
+#[test]
+fn test_create_order_via_path_payment_forwards_multi_hop_path() {
+    let (env, client, buyer, farmer, _, source_token, settlement_token, _) =
+        setup_path_payment_test(1_000, 995);
+    let router = MockRouterClient::new(&env, &client.get_path_payment_router().unwrap());
+    let path = Vec::from_array(&env, [Address::generate(&env), Address::generate(&env)]);
+
+    client.create_order_via_path_payment(
+        &buyer,
+        &farmer,
+        &source_token.address,
+        &1_000,
+        &settlement_token.address,
+        &990,
+        &path,
+        &None,
+    );
+    assert_eq!(router.last_path(), path);
+}
+
+#[test]
+fn test_create_order_via_path_receive_refunds_unspent_source() {
+    // The router quotes 1000 source for the 800 settlement and really spends
+    // 1005. With 1% tolerance it may pull at most 1010 of the buyer's 2000.
+    let (env, client, buyer, farmer, fee_collector, source_token, settlement_token, _) =
+        setup_path_payment_test(1_000, 1_005);
+    let router = MockRouterClient::new(&env, &client.get_path_payment_router().unwrap());
+    let path = Vec::from_array(&env, [Address::generate(&env)]);
+
+    let order_id = client.create_order_via_path_receive(
+        &buyer,
+        &farmer,
+        &source_token.address,
+        &2_000,
+        &settlement_token.address,
+        &800,
+        &path,
+        &None,
+    );
+
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.amount, 800 - 24);
+    assert_eq!(order.fee, 24);
+    assert_eq!(source_token.balance(&buyer), 10_000 - 1_005);
+    assert_eq!(source_token.balance(&client.address), 0);
+    assert_eq!(settlement_token.balance(&fee_collector), 0);
+    assert_eq!(router.last_path(), path);
+}
+
+#[test]
+fn test_create_order_via_path_receive_caps_spend_at_slippage_tolerance() {
+    // Spending 1050 against a 1000 quote exceeds the 1% tolerance, so the
+    // router's pull overruns its allowance and the whole order reverts.
+    let (env, client, buyer, farmer, _, source_token, settlement_token, _) =
+        setup_path_payment_test(1_000, 1_050);
+
+    let result = client.try_create_order_via_path_receive(
+        &buyer,
+        &farmer,
+        &source_token.address,
+        &2_000,
+        &settlement_token.address,
+        &800,
+        &Vec::new(&env),
+        &None,
+    );
+    assert!(result.is_err());
+    assert_eq!(source_token.balance(&buyer), 10_000);
+}
+
+/// Registers a further mock router quoting `quote`, holding `liquidity` of
+/// `dest_token`, and allow-lists it on the escrow.
+fn add_mock_router(
+    env: &Env,
+    client: &EscrowContractClient,
+    admin: &Address,
+    dest_token: &Address,
+    quote: i128,
+    actual: i128,
+    liquidity: i128,
+) -> Address {
+    let router_id = env.register(MockRouter, ());
+    MockRouterClient::new(env, &router_id).configure(&quote, &actual);
+    token::StellarAssetClient::new(env, dest_token).mint(&router_id, &liquidity);
+    client.add_path_payment_router(admin, &router_id);
+    router_id
+}
+
+#[test]
+fn test_create_order_via_path_payment_executes_against_best_quote() {
+    let (env, client, buyer, farmer, _, source_token, settlement_token, admin) =
+        setup_path_payment_test(1_000, 990);
+    let first = client.get_path_payment_routers().get(0).unwrap();
+    let best = add_mock_router(&env, &client, &admin, &settlement_token.address, 1_100, 1_100, 1_100);
+    assert_eq!(client.get_path_payment_routers().len(), 2);
+    assert_eq!(client.get_path_payment_router(), Some(first.clone()));
+
+    let order_id = client.create_order_via_path_payment(
+        &buyer,
+        &farmer,
+        &source_token.address,
+        &1_000,
+        &settlement_token.address,
+        &1,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_order_details(&order_id).amount, 1_100 - 33);
+    assert_eq!(source_token.balance(&best), 1_000);
+    assert_eq!(settlement_token.balance(&first), 990);
+
+    // Once the better router is delisted, orders fall back to the other one.
+    client.remove_path_payment_router(&admin, &best);
+    assert_eq!(
+        client.try_remove_path_payment_router(&admin, &best).unwrap_err().unwrap(),
+        EscrowError::RouterNotConfigured
+    );
+    let order_id = client.create_order_via_path_payment(
+        &buyer,
+        &farmer,
+        &source_token.address,
+        &1_000,
+        &settlement_token.address,
+        &1,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_order_details(&order_id).amount, 990 - 29);
+    assert_eq!(source_token.balance(&first), 1_000);
+}
+
+#[test]
+fn test_pair_routing_restricts_routers_and_overrides_slippage() {
+    let (env, client, buyer, farmer, _, source_token, settlement_token, admin) =
+        setup_path_payment_test(1_000, 990);
+    let preferred = client.get_path_payment_routers().get(0).unwrap();
+    add_mock_router(&env, &client, &admin, &settlement_token.address, 1_100, 1_100, 1_100);
+
+    let unlisted = Address::generate(&env);
+    assert_eq!(
+        client
+            .try_set_pair_routing(
+                &admin,
+                &source_token.address,
+                &settlement_token.address,
+                &Vec::from_array(&env, [unlisted]),
+                &None,
+            )
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::RouterNotConfigured
+    );
+    assert_eq!(
+        client
+            .try_set_pair_routing(
+                &admin,
+                &source_token.address,
+                &settlement_token.address,
+                &Vec::new(&env),
+                &Some(10_001),
+            )
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::InvalidSlippageTolerance
+    );
+
+    // Pinned to the preferred router with zero tolerance, its 1% shortfall
+    // against its own quote is rejected even though the global cap allows it.
+    let pinned = Vec::from_array(&env, [preferred.clone()]);
+    client.set_pair_routing(
+        &admin,
+        &source_token.address,
+        &settlement_token.address,
+        &pinned,
+        &Some(0),
+    );
+    let create = || {
+        client.try_create_order_via_path_payment(
+            &buyer,
+            &farmer,
+            &source_token.address,
+            &1_000,
+            &settlement_token.address,
+            &1,
+            &Vec::new(&env),
+            &None,
+        )
+    };
+    assert_eq!(
+        create().unwrap_err().unwrap(),
+        EscrowError::SlippageToleranceExceeded
+    );
+
+    client.set_pair_routing(
+        &admin,
+        &source_token.address,
+        &settlement_token.address,
+        &pinned,
+        &Some(200),
+    );
+    let order_id = create().unwrap().unwrap();
+    assert_eq!(client.get_order_details(&order_id).amount, 990 - 29);
+    assert_eq!(source_token.balance(&preferred), 1_000);
+
+    client.set_pair_routing(
+        &admin,
+        &source_token.address,
+        &settlement_token.address,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(
+        client.get_pair_routing(&source_token.address, &settlement_token.address),
+        None
+    );
+}
+
+#[test]
+fn test_create_order_via_path_receive_uses_cheapest_router() {
+    let (env, client, buyer, farmer, _, source_token, settlement_token, admin) =
+        setup_path_payment_test(1_000, 1_005);
+    let cheapest = add_mock_router(&env, &client, &admin, &settlement_token.address, 900, 900, 800);
+
+    let order_id = client.create_order_via_path_receive(
+        &buyer,
+        &farmer,
+        &source_token.address,
+        &2_000,
+        &settlement_token.address,
+        &800,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_order_details(&order_id).amount, 800 - 24);
+    assert_eq!(source_token.balance(&cheapest), 900);
+    assert_eq!(source_token.balance(&buyer), 10_000 - 900);
+}
+
+/// Registers a local payout token for `farmer` and a router converting the
+/// escrow token into it, funded with `liquidity` of the payout token.
+fn setup_farmer_payout(
+    env: &Env,
+    client: &EscrowContractClient,
+    admin: &Address,
+    farmer: &Address,
+    quote: i128,
+    actual_out: i128,
+    liquidity: i128,
+) -> (Address, token::Client<'static>) {
+    let local_token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let router_id = env.register(MockRouter, ());
+    MockRouterClient::new(env, &router_id).configure(&quote, &actual_out);
+    if liquidity > 0 {
+        token::StellarAssetClient::new(env, &local_token).mint(&router_id, &liquidity);
+    }
+    client.set_path_payment_router(admin, &router_id);
+    let mut supported_tokens = client.get_supported_tokens();
+    supported_tokens.push_back(local_token.clone());
+    client.set_supported_tokens(admin, &supported_tokens);
+    client.set_farmer_payout_token(farmer, &Some(local_token.clone()));
+    (router_id, token::Client::new(env, &local_token))
+}
+
+#[test]
+fn test_confirm_receipt_converts_farmer_payout() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, _) = setup_test();
+    let (router, local_token) = setup_farmer_payout(&env, &client, &admin, &farmer, 485, 483, 483);
+    assert_eq!(client.get_farmer_payout_token(&farmer), Some(local_token.address.clone()));
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.confirm_receipt(&buyer, &order_id);
+
+    assert_eq!(local_token.balance(&farmer), 483);
+    assert_eq!(token.balance(&farmer), 0);
+    assert_eq!(token.balance(&router), 485);
+    // The fee is still collected in the escrow token.
+    assert_eq!(token.balance(&collector), 15);
+}
+
+#[test]
+fn test_farmer_payout_falls_back_to_escrow_token_when_swap_fails() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    // The router has no payout-token liquidity, so its swap reverts.
+    let (router, local_token) = setup_farmer_payout(&env, &client, &admin, &farmer, 485, 483, 0);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.open_dispute(
+        &buyer,
+        &order_id,
+        &String::from_str(&env, "late"),
+        &String::from_str(&env, "QmHash"),
+    );
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    finalize_ruling(&env, &client, order_id);
+
+    assert_eq!(token.balance(&farmer), 485);
+    assert_eq!(token.balance(&router), 0);
+    assert_eq!(local_token.balance(&farmer), 0);
+
+    client.set_farmer_payout_token(&farmer, &None);
+    assert_eq!(client.get_farmer_payout_token(&farmer), None);
+}
+
+#[test]
+fn test_farmer_clears_payout_token_when_router_under_delivers() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+    // The router quotes 485 but fills only 400, past the 1% tolerance.
+    let (router, local_token) = setup_farmer_payout(&env, &client, &admin, &farmer, 485, 400, 400);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    let result = client.try_confirm_receipt(&buyer, &order_id);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        EscrowError::SlippageToleranceExceeded
+    );
+    // The swap was rolled back with the payout.
+    assert_eq!(token.balance(&contract_id), 500);
+    assert_eq!(token.balance(&router), 0);
+    assert_eq!(local_token.balance(&router), 400);
+
+    client.set_farmer_payout_token(&farmer, &None);
+    client.confirm_receipt(&buyer, &order_id);
+    assert_eq!(token.balance(&farmer), 485);
+    assert_eq!(local_token.balance(&farmer), 0);
+}
+
+#[test]
+fn test_farmer_payout_token_must_be_supported() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    let unlisted = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let result = client.try_set_farmer_payout_token(&farmer, &Some(unlisted));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::UnsupportedToken);
+
+    // A payout token delisted after registration is paid out in the escrow
+    // token instead of being swapped into.
+    let (router, local_token) = setup_farmer_payout(&env, &client, &admin, &farmer, 485, 483, 483);
+    client.set_supported_tokens(
+        &admin,
+        &Vec::from_array(&env, [token.address.clone(), usdc.address.clone()]),
+    );
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.confirm_receipt(&buyer, &order_id);
+
+    assert_eq!(token.balance(&farmer), 485);
+    assert_eq!(token.balance(&router), 0);
+    assert_eq!(local_token.balance(&farmer), 0);
+}
+
 // ---------------------------------------------------------------------------
 // On-chain reputation reporting (Issue #592)
 // ---------------------------------------------------------------------------
@@ -1278,6 +1840,27 @@ impl MockRegistry {
             .get(&symbol_short!("calls"))
             .unwrap_or_else(|| Vec::new(&env))
     }
+
+    pub fn set_score(env: Env, farmer: Address, score: i64) {
+        env.storage().instance().set(&farmer, &score);
+    }
+
+    pub fn get_reputation(env: Env, farmer: Address) -> MockReputation {
+        MockReputation {
+            score: env.storage().instance().get(&farmer).unwrap_or(0),
+            completed_orders: 0,
+            disputed_orders: 0,
+        }
+    }
+}
+
+/// Mirrors the registry's `ReputationRecord` layout.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockReputation {
+    pub score: i64,
+    pub completed_orders: u32,
+    pub disputed_orders: u32,
 }
 
 #[test]
@@ -1292,7 +1875,8 @@ fn test_confirm_receipt_reports_completed_outcome_to_registry() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
     client.mock_all_auths().confirm_receipt(&buyer, &order_id);
 
     let calls = registry_client.calls();
@@ -1315,7 +1899,8 @@ fn test_resolve_dispute_reports_split_outcome_to_registry() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
     let reason = String::from_str(&env, "bad batch");
     let evidence_hash = String::from_str(&env, "hash");
     client
@@ -1324,6 +1909,7 @@ fn test_resolve_dispute_reports_split_outcome_to_registry() {
     client
         .mock_all_auths()
         .resolve_dispute(&admin, &order_id, &DisputeResolution::Split(3_000));
+    finalize_ruling(&env, &client, order_id);
 
     let calls = registry_client.calls();
     assert_eq!(calls.len(), 1);
@@ -1338,7 +1924,8 @@ fn test_confirm_receipt_without_registry_configured_still_succeeds() {
 
     let order_id = client
         .mock_all_auths()
-        .create_order(&buyer, &farmer, &token.address, &500);
+        .create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.mock_all_auths().accept_order(&farmer, &order_id);
     client.mock_all_auths().confirm_receipt(&buyer, &order_id);
 
     let order = client.get_order_details(&order_id);
@@ -1386,6 +1973,9 @@ fn test_create_split_order_validates_shares() {
         &token.address,
         &co_buyers,
         &shares,
+        &None,
+        &None,
+        &None,
     );
     assert_eq!(
         result.unwrap_err().unwrap(),
@@ -1407,6 +1997,9 @@ fn test_split_order_partial_funding_stays_in_funding_state() {
         &token.address,
         &co_buyers,
         &shares,
+        &None,
+        &None,
+        &None,
     );
 
     // Only the first co-buyer funds their share.
@@ -1423,63 +2016,259 @@ fn test_split_order_partial_funding_stays_in_funding_state() {
 }
 
 #[test]
-fn test_split_order_becomes_active_once_fully_funded() {
-    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
-    let mut shares = Vec::new(&env);
-    shares.push_back(500i128);
-    shares.push_back(500i128);
-
-    let order_id = client.mock_all_auths().create_split_order(
-        &co_buyers.get(0).unwrap(),
+fn test_split_order_unwinds_after_funding_deadline() {
+    let (env, client, farmer, co_buyers, token, contract_id, _admin) = setup_split_test(3);
+    env.mock_all_auths();
+    let (first, second, third) = (
+        co_buyers.get(0).unwrap(),
+        co_buyers.get(1).unwrap(),
+        co_buyers.get(2).unwrap(),
+    );
+    let order_id = client.create_split_order(
+        &first,
         &farmer,
         &token.address,
         &co_buyers,
-        &shares,
+        &Vec::from_array(&env, [300i128, 300i128, 400i128]),
+        &None,
+        &Some(DAY),
+        &None,
     );
-    client
-        .mock_all_auths()
-        .fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
-    let mid = client.get_split_order(&order_id);
-    assert_eq!(mid.status, SplitOrderStatus::Funding);
+    client.fund_split_order(&first, &order_id);
+    client.fund_split_order(&second, &order_id);
+    assert_eq!(
+        client.try_reclaim_split_share(&first, &order_id).unwrap_err().unwrap(),
+        EscrowError::WindowNotElapsed
+    );
+    for funding_window in [0, 30 * DAY + 1] {
+        let result = client.try_create_split_order(
+            &first,
+            &farmer,
+            &token.address,
+            &co_buyers,
+            &Vec::from_array(&env, [300i128, 300i128, 400i128]),
+            &None,
+            &Some(funding_window),
+            &None,
+        );
+        assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidFundingWindow);
+    }
 
-    client
-        .mock_all_auths()
-        .fund_split_order(&co_buyers.get(1).unwrap(), &order_id);
-    let order = client.get_split_order(&order_id);
-    assert_eq!(order.status, SplitOrderStatus::Active);
-    // 3% platform fee taken once on full funding: 1000 - 30 = 970.
-    assert_eq!(order.net_amount, 970);
+    env.ledger().set_timestamp(env.ledger().timestamp() + DAY + 1);
+    assert_eq!(
+        client.try_fund_split_order(&third, &order_id).unwrap_err().unwrap(),
+        EscrowError::WindowClosed
+    );
+    assert_eq!(
+        client.try_reclaim_split_share(&third, &order_id).unwrap_err().unwrap(),
+        EscrowError::NotCoBuyer
+    );
+
+    assert_eq!(client.reclaim_split_share(&first, &order_id), 300);
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Funding);
+    assert_eq!(client.reclaim_split_share(&second, &order_id), 300);
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Refunded);
+    assert_eq!(token.balance(&first), 1_000);
+    assert_eq!(token.balance(&second), 1_000);
+    assert_eq!(token.balance(&contract_id), 0);
 }
 
 #[test]
-fn test_split_order_fund_twice_fails() {
-    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
-    let mut shares = Vec::new(&env);
-    shares.push_back(500i128);
-    shares.push_back(500i128);
-
-    let order_id = client.mock_all_auths().create_split_order(
-        &co_buyers.get(0).unwrap(),
+fn test_split_order_shortfall_absorbed_after_funding_deadline() {
+    let (env, client, farmer, co_buyers, token, contract_id, _admin) = setup_split_test(3);
+    env.mock_all_auths();
+    let (first, second, third) = (
+        co_buyers.get(0).unwrap(),
+        co_buyers.get(1).unwrap(),
+        co_buyers.get(2).unwrap(),
+    );
+    let order_id = client.create_split_order(
+        &first,
         &farmer,
         &token.address,
         &co_buyers,
-        &shares,
+        &Vec::from_array(&env, [300i128, 300i128, 400i128]),
+        &None,
+        &None,
+        &None,
     );
-    client
-        .mock_all_auths()
-        .fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
-    let result = client
-        .mock_all_auths()
-        .try_fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
-    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyContributed);
-}
+    client.fund_split_order(&first, &order_id);
+    client.fund_split_order(&second, &order_id);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 7 * DAY + 1);
+    assert_eq!(client.absorb_split_shortfall(&second, &order_id), 400);
+
+    let order = client.get_split_order(&order_id);
+    assert_eq!(order.status, SplitOrderStatus::Active);
+    assert_eq!(order.co_buyers, Vec::from_array(&env, [first.clone(), second.clone()]));
+    assert_eq!(order.shares.get(second.clone()), Some(700));
+    assert!(!order.shares.contains_key(third.clone()));
+    assert_eq!(order.net_amount, 970);
+    assert_eq!(order.fee, 30);
+    assert_eq!(token.balance(&second), 1_000 - 700);
+    // The fee stays in escrow with the net amount until the order settles.
+    assert_eq!(token.balance(&contract_id), 1_000);
+
+    client.confirm_split_receipt(&second, &order_id);
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Completed);
+    assert_eq!(token.balance(&farmer), 970);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_transfer_split_share_moves_slot_without_confirmation() {
+    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(3);
+    env.mock_all_auths();
+    let (first, second, third) = (
+        co_buyers.get(0).unwrap(),
+        co_buyers.get(1).unwrap(),
+        co_buyers.get(2).unwrap(),
+    );
+    let order_id = client.create_split_order(
+        &first,
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [300i128, 300i128, 400i128]),
+        &None,
+        &None,
+        &None,
+    );
+    client.fund_split_order(&first, &order_id);
+    assert_eq!(
+        client.try_transfer_split_share(&first, &second, &order_id).unwrap_err().unwrap(),
+        EscrowError::AlreadyContributed
+    );
+
+    // A funded slot carries its funding over to the new co-buyer.
+    let newcomer = Address::generate(&env);
+    client.transfer_split_share(&first, &newcomer, &order_id);
+    let order = client.get_split_order(&order_id);
+    assert_eq!(
+        order.co_buyers,
+        Vec::from_array(&env, [newcomer.clone(), second.clone(), third.clone()])
+    );
+    assert_eq!(order.shares.get(newcomer.clone()), Some(300));
+    assert_eq!(order.funded.get(newcomer.clone()), Some(true));
+    assert!(!order.shares.contains_key(first.clone()));
+    assert_eq!(
+        client.try_fund_split_order(&newcomer, &order_id).unwrap_err().unwrap(),
+        EscrowError::AlreadyContributed
+    );
+
+    client.fund_split_order(&second, &order_id);
+    client.fund_split_order(&third, &order_id);
+    client.confirm_split_receipt(&second, &order_id);
+
+    // The recorded confirmation stays behind with the departing co-buyer.
+    let replacement = Address::generate(&env);
+    client.transfer_split_share(&second, &replacement, &order_id);
+    let order = client.get_split_order(&order_id);
+    assert_eq!(order.confirmed_count, 0);
+    assert_eq!(order.confirmed_value, 0);
+    assert_eq!(order.confirmed.get(replacement.clone()), None);
+
+    client.confirm_split_receipt(&replacement, &order_id);
+    client.confirm_split_receipt(&third, &order_id);
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Completed);
+    assert_eq!(token.balance(&first), 1_000 - 300);
+}
+
+#[test]
+fn test_farmer_declines_split_order_before_funding_completes() {
+    let (env, client, farmer, co_buyers, token, contract_id, _admin) = setup_split_test(2);
+    env.mock_all_auths();
+    let first = co_buyers.get(0).unwrap();
+    let order_id = client.create_split_order(
+        &first,
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [500i128, 500i128]),
+        &None,
+        &None,
+        &None,
+    );
+    client.fund_split_order(&first, &order_id);
+    assert_eq!(
+        client.try_decline_split_order(&first, &order_id).unwrap_err().unwrap(),
+        EscrowError::NotFarmer
+    );
+
+    client.decline_split_order(&farmer, &order_id);
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Refunded);
+    assert_eq!(token.balance(&first), 1_000);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(
+        client.try_fund_split_order(&co_buyers.get(1).unwrap(), &order_id).unwrap_err().unwrap(),
+        EscrowError::SplitOrderAlreadyFunded
+    );
+}
+
+#[test]
+fn test_split_order_becomes_active_once_fully_funded() {
+    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
+    let mut shares = Vec::new(&env);
+    shares.push_back(500i128);
+    shares.push_back(500i128);
+
+    let order_id = client.mock_all_auths().create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &shares,
+        &None,
+        &None,
+        &None,
+    );
+    client
+        .mock_all_auths()
+        .fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
+    let mid = client.get_split_order(&order_id);
+    assert_eq!(mid.status, SplitOrderStatus::Funding);
+
+    client
+        .mock_all_auths()
+        .fund_split_order(&co_buyers.get(1).unwrap(), &order_id);
+    let order = client.get_split_order(&order_id);
+    assert_eq!(order.status, SplitOrderStatus::Active);
+    // 3% platform fee worked out once on full funding: 1000 - 30 = 970.
+    assert_eq!(order.net_amount, 970);
+}
+
+#[test]
+fn test_split_order_fund_twice_fails() {
+    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
+    let mut shares = Vec::new(&env);
+    shares.push_back(500i128);
+    shares.push_back(500i128);
+
+    let order_id = client.mock_all_auths().create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &shares,
+        &None,
+        &None,
+        &None,
+    );
+    client
+        .mock_all_auths()
+        .fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
+    let result = client
+        .mock_all_auths()
+        .try_fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyContributed);
+}
 
 #[test]
 fn test_split_order_majority_by_value_releases_despite_non_confirming_contributor() {
     // Shares: 600 / 200 / 200. The 600-share co-buyer alone is a strict
     // majority by value, so the order completes even though the other two
     // co-buyers never confirm.
-    let (env, client, farmer, co_buyers, token, contract_id, _admin) = setup_split_test(3);
+    let (env, client, farmer, co_buyers, token, contract_id, admin) = setup_split_test(3);
     let mut shares = Vec::new(&env);
     shares.push_back(600i128);
     shares.push_back(200i128);
@@ -1491,13 +2280,16 @@ fn test_split_order_majority_by_value_releases_despite_non_confirming_contributo
         &token.address,
         &co_buyers,
         &shares,
+        &None,
+        &None,
+        &None,
     );
     for co_buyer in co_buyers.iter() {
         client.mock_all_auths().fund_split_order(&co_buyer, &order_id);
     }
     client
         .mock_all_auths()
-        .mark_split_delivered(&farmer, &order_id);
+        .mark_split_delivered(&farmer, &admin, &order_id);
 
     // Only the majority-share (non-confirming-others) co-buyer confirms.
     client
@@ -1514,7 +2306,7 @@ fn test_split_order_majority_by_value_releases_despite_non_confirming_contributo
 fn test_split_order_even_split_requires_unanimous_confirmation() {
     // Shares: 500 / 500. Neither co-buyer alone is a strict majority, so
     // both must confirm before the farmer is paid.
-    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
+    let (env, client, farmer, co_buyers, token, _, admin) = setup_split_test(2);
     let mut shares = Vec::new(&env);
     shares.push_back(500i128);
     shares.push_back(500i128);
@@ -1525,13 +2317,16 @@ fn test_split_order_even_split_requires_unanimous_confirmation() {
         &token.address,
         &co_buyers,
         &shares,
+        &None,
+        &None,
+        &None,
     );
     for co_buyer in co_buyers.iter() {
         client.mock_all_auths().fund_split_order(&co_buyer, &order_id);
     }
     client
         .mock_all_auths()
-        .mark_split_delivered(&farmer, &order_id);
+        .mark_split_delivered(&farmer, &admin, &order_id);
 
     client
         .mock_all_auths()
@@ -1562,6 +2357,9 @@ fn test_split_order_dispute_refund_is_pro_rata_across_all_contributors() {
         &token.address,
         &co_buyers,
         &shares,
+        &None,
+        &None,
+        &None,
     );
     for co_buyer in co_buyers.iter() {
         client.mock_all_auths().fund_split_order(&co_buyer, &order_id);
@@ -1582,14 +2380,145 @@ fn test_split_order_dispute_refund_is_pro_rata_across_all_contributors() {
 
     let order = client.get_split_order(&order_id);
     assert_eq!(order.status, SplitOrderStatus::Refunded);
-    // net_amount = 1000 - 3% fee = 970, pro-rata over shares 500/300/200.
-    assert_eq!(token.balance(&co_buyers.get(0).unwrap()), 1_000 - 500 + 485);
-    assert_eq!(token.balance(&co_buyers.get(1).unwrap()), 1_000 - 300 + 291);
-    assert_eq!(token.balance(&co_buyers.get(2).unwrap()), 1_000 - 200 + 194);
+    // net_amount and the escrowed fee both come back, pro-rata over shares
+    // 500/300/200.
+    assert_eq!(token.balance(&co_buyers.get(0).unwrap()), 1_000);
+    assert_eq!(token.balance(&co_buyers.get(1).unwrap()), 1_000);
+    assert_eq!(token.balance(&co_buyers.get(2).unwrap()), 1_000);
     assert_eq!(token.balance(&contract_id), 0);
     assert_eq!(token.balance(&farmer), 0);
 }
 
+#[test]
+fn test_split_dispute_divides_escrowed_fee_with_the_ruling() {
+    let (env, client, farmer, co_buyers, token, contract_id, admin) = setup_split_test(2);
+    env.mock_all_auths();
+    let agent = Address::generate(&env);
+    client.set_referral_share_bps(&admin, &5_000);
+    let order_id = client.create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [500i128, 500i128]),
+        &None,
+        &None,
+        &Some(agent.clone()),
+    );
+    for co_buyer in co_buyers.iter() {
+        client.fund_split_order(&co_buyer, &order_id);
+    }
+    let collector = client.get_fee_collector();
+    assert_eq!(token.balance(&collector), 0);
+
+    client.open_split_dispute(
+        &co_buyers.get(1).unwrap(),
+        &order_id,
+        &String::from_str(&env, "a fifth of the sacks were short"),
+        &String::from_str(&env, "hash"),
+    );
+    client.resolve_split_dispute(&admin, &order_id, &DisputeResolution::Split(2_000));
+
+    // 20% of the 970 net and 30 fee goes back: 100 to each co-buyer. The
+    // farmer keeps 776 and the remaining 24 fee is split with the referrer.
+    assert_eq!(token.balance(&co_buyers.get(0).unwrap()), 1_000 - 500 + 100);
+    assert_eq!(token.balance(&co_buyers.get(1).unwrap()), 1_000 - 500 + 100);
+    assert_eq!(token.balance(&farmer), 776);
+    assert_eq!(client.get_referral_rewards(&agent, &token.address), 12);
+    assert_eq!(token.balance(&collector), 12);
+    assert_eq!(token.balance(&contract_id), 12);
+}
+
+#[test]
+fn test_undelivered_split_order_refunds_pro_rata_after_expiry() {
+    let (env, client, farmer, co_buyers, token, contract_id, _admin) = setup_split_test(3);
+    env.mock_all_auths();
+    let order_id = client.create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [500i128, 300i128, 200i128]),
+        &None,
+        &None,
+        &None,
+    );
+    for co_buyer in co_buyers.iter() {
+        client.fund_split_order(&co_buyer, &order_id);
+    }
+
+    // Delivery needs the attester's co-signature.
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_mark_split_delivered(&farmer, &stranger, &order_id).unwrap_err().unwrap(),
+        EscrowError::NotAttester
+    );
+    assert_eq!(
+        client.try_refund_expired_split_order(&co_buyers.get(1).unwrap(), &order_id).unwrap_err().unwrap(),
+        EscrowError::OrderNotExpired
+    );
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 96 * 60 * 60 + 1);
+    assert_eq!(
+        client.try_refund_expired_split_order(&stranger, &order_id).unwrap_err().unwrap(),
+        EscrowError::NotCoBuyer
+    );
+    client.refund_expired_split_order(&co_buyers.get(1).unwrap(), &order_id);
+
+    // The fee was still held in escrow, so it is refunded with net_amount.
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Refunded);
+    assert_eq!(token.balance(&co_buyers.get(0).unwrap()), 1_000);
+    assert_eq!(token.balance(&co_buyers.get(1).unwrap()), 1_000);
+    assert_eq!(token.balance(&co_buyers.get(2).unwrap()), 1_000);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_split_dispute_resolved_by_arbitrator_quorum() {
+    let (env, client, farmer, co_buyers, token, _, admin) = setup_split_test(2);
+    env.mock_all_auths();
+    let arbitrators = stake_test_arbitrators(&env, &client, &admin, &token.address, 3, 3, 2);
+    let order_id = client.create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [500i128, 500i128]),
+        &None,
+        &None,
+        &None,
+    );
+    for co_buyer in co_buyers.iter() {
+        client.fund_split_order(&co_buyer, &order_id);
+    }
+    client.open_split_dispute(
+        &co_buyers.get(0).unwrap(),
+        &order_id,
+        &String::from_str(&env, "short weight"),
+        &String::from_str(&env, "hash"),
+    );
+
+    assert_eq!(client.get_split_dispute_panel(&order_id).len(), 3);
+    assert_eq!(
+        client.try_resolve_split_dispute(&admin, &order_id, &DisputeResolution::Refund).unwrap_err().unwrap(),
+        EscrowError::WindowNotElapsed
+    );
+
+    client.vote_to_resolve_split(&arbitrators.get(0).unwrap(), &order_id, &DisputeResolution::Release);
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Disputed);
+    client.vote_to_resolve_split(&arbitrators.get(1).unwrap(), &order_id, &DisputeResolution::Release);
+
+    assert_eq!(client.get_split_order(&order_id).status, SplitOrderStatus::Completed);
+    assert_eq!(token.balance(&farmer), 970);
+    assert_eq!(
+        client.try_vote_to_resolve_split(&arbitrators.get(2).unwrap(), &order_id, &DisputeResolution::Refund).unwrap_err().unwrap(),
+        EscrowError::OrderNotDisputed
+    );
+    for arbitrator in arbitrators.iter() {
+        assert_eq!(client.get_arbitrator_stake(&arbitrator).open_panels, 0);
+    }
+}
+
 #[test]
 fn test_fund_split_order_non_co_buyer_fails() {
     let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
@@ -1602,6 +2531,9 @@ fn test_fund_split_order_non_co_buyer_fails() {
         &token.address,
         &co_buyers,
         &shares,
+        &None,
+        &None,
+        &None,
     );
 
     let stranger = Address::generate(&env);
@@ -1610,3 +2542,2071 @@ fn test_fund_split_order_non_co_buyer_fails() {
         .try_fund_split_order(&stranger, &order_id);
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotCoBuyer);
 }
+
+// ── Quantity-priced orders and partial delivery ─────────────────────────────
+
+#[test]
+fn test_create_quantity_order_records_terms() {
+    let (_env, client, buyer, farmer, collector, token, _, _, _, contract_id) = setup_test();
+
+    // 200 kg at 4 per kg = 800 gross; 3% fee = 24.
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &200,
+        &symbol_short!("kg"),
+        &4,
+        &None,
+    );
+
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.quantity, 200);
+    assert_eq!(order.unit, symbol_short!("kg"));
+    assert_eq!(order.unit_price, 4);
+    assert_eq!(order.amount, 776);
+    assert_eq!(order.fee, 24);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), 800);
+}
+
+#[test]
+fn test_create_order_records_single_lot() {
+    let (_env, client, _admin, buyer, farmer, token) = create_test_with_tokens();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.quantity, 1);
+    assert_eq!(order.unit, symbol_short!("lot"));
+    assert_eq!(order.unit_price, 500);
+}
+
+#[test]
+fn test_partial_delivery_releases_pro_rata() {
+    let (_env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+
+    // 100 units at 10 = 1000 gross, 970 net.
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &100,
+        &symbol_short!("kg"),
+        &10,
+        &None,
+    );
+    client.accept_order(&farmer, &order_id);
+
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &40);
+    client.confirm_partial_receipt(&buyer, &order_id, &40);
+
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.status, OrderStatus::Pending);
+    assert_eq!(order.delivered_quantity, 40);
+    assert_eq!(order.confirmed_quantity, 40);
+    assert_eq!(token.balance(&farmer), 388);
+
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &60);
+    client.confirm_partial_receipt(&buyer, &order_id, &60);
+
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.status, OrderStatus::Completed);
+    assert_eq!(token.balance(&farmer), 970);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_confirm_partial_receipt_beyond_delivered_fails() {
+    let (_env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &100,
+        &symbol_short!("kg"),
+        &10,
+        &None,
+    );
+    client.accept_order(&farmer, &order_id);
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &30);
+
+    let result = client.try_confirm_partial_receipt(&buyer, &order_id, &31);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        EscrowError::QuantityExceedsOutstanding
+    );
+}
+
+#[test]
+fn test_mark_partial_delivery_beyond_ordered_quantity_fails() {
+    let (_env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &100,
+        &symbol_short!("kg"),
+        &10,
+        &None,
+    );
+    client.accept_order(&farmer, &order_id);
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &80);
+
+    let result = client.try_mark_partial_delivery(&farmer, &admin, &order_id, &21);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        EscrowError::QuantityExceedsOutstanding
+    );
+
+    // mark_delivered delivers exactly the 20 units still outstanding.
+    client.mark_delivered(&farmer, &admin, &order_id);
+    assert_eq!(client.get_order_details(&order_id).delivered_quantity, 100);
+}
+
+#[test]
+fn test_expired_partial_delivery_refunds_undelivered_remainder() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, contract_id) = setup_test();
+
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &100,
+        &symbol_short!("kg"),
+        &10,
+        &None,
+    );
+    client.accept_order(&farmer, &order_id);
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &25);
+
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 345_601);
+    client.refund_expired_order(&buyer, &order_id);
+
+    // 75 of 100 units undelivered: 75% of the 970 net and of the 30 fee is
+    // refunded, 25% of each stays escrowed.
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.status, OrderStatus::Pending);
+    assert!(order.remainder_refunded);
+    assert_eq!(token.balance(&buyer), 751);
+    assert_eq!(token.balance(&contract_id), 249);
+
+    // No further lots can be delivered once the remainder was refunded.
+    let result = client.try_mark_partial_delivery(&farmer, &admin, &order_id, &10);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotPending);
+
+    client.confirm_partial_receipt(&buyer, &order_id, &25);
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.status, OrderStatus::Completed);
+    assert_eq!(token.balance(&farmer), 242);
+    assert_eq!(token.balance(&collector), 7);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_dispute_after_partial_confirmation_splits_remaining_escrow() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &100,
+        &symbol_short!("kg"),
+        &10,
+        &None,
+    );
+    client.accept_order(&farmer, &order_id);
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &50);
+    client.confirm_partial_receipt(&buyer, &order_id, &50);
+    assert_eq!(token.balance(&farmer), 485);
+
+    let reason = String::from_str(&env, "second lot spoiled");
+    let evidence_hash = String::from_str(&env, "hash");
+    client.open_dispute(&buyer, &order_id, &reason, &evidence_hash);
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    finalize_ruling(&env, &client, order_id);
+
+    // Only the unconfirmed half (and its fee) is refunded; the farmer keeps
+    // the first lot.
+    assert_eq!(token.balance(&buyer), 500);
+    assert_eq!(token.balance(&farmer), 485);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+// ── Recurring subscription orders ───────────────────────────────────────────
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+#[test]
+fn test_create_subscription_prefunds_and_spawns_first_order() {
+    let (_env, client, buyer, farmer, collector, token, _, _, _, contract_id) = setup_test();
+
+    let sub_id = client.create_subscription(&buyer, &farmer, &token.address, &100, &WEEK, &4);
+
+    let sub = client.get_subscription(&sub_id);
+    assert_eq!(sub.status, SubscriptionStatus::Active);
+    assert_eq!(sub.spawned_periods, 1);
+    assert_eq!(sub.balance, 300);
+    assert_eq!(sub.order_ids.len(), 1);
+
+    let order = client.get_order_details(&sub.order_ids.get(0).unwrap());
+    assert_eq!(order.buyer, buyer);
+    assert_eq!(order.farmer, farmer);
+    assert_eq!(order.amount, 97);
+    assert_eq!(order.fee, 3);
+    assert_eq!(order.status, OrderStatus::Awaiting);
+
+    assert_eq!(token.balance(&buyer), 600);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), 400);
+}
+
+#[test]
+fn test_process_subscription_before_period_due_fails() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let sub_id = client.create_subscription(&buyer, &farmer, &token.address, &100, &WEEK, &4);
+    env.ledger().set_timestamp(env.ledger().timestamp() + WEEK - 1);
+
+    let result = client.try_process_subscription(&sub_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowNotElapsed);
+}
+
+#[test]
+fn test_subscription_spawns_each_period_until_completed() {
+    let (env, client, buyer, farmer, _, token, _, _, _, contract_id) = setup_test();
+
+    let sub_id = client.create_subscription(&buyer, &farmer, &token.address, &100, &WEEK, &3);
+    for _ in 0..2 {
+        env.ledger().set_timestamp(env.ledger().timestamp() + WEEK);
+        client.process_subscription(&sub_id);
+    }
+
+    let sub = client.get_subscription(&sub_id);
+    assert_eq!(sub.status, SubscriptionStatus::Completed);
+    assert_eq!(sub.balance, 0);
+    assert_eq!(sub.order_ids.len(), 3);
+
+    // Each spawned order settles through the regular lifecycle.
+    for order_id in sub.order_ids.iter() {
+        client.accept_order(&farmer, &order_id);
+        client.confirm_receipt(&buyer, &order_id);
+    }
+    assert_eq!(token.balance(&farmer), 3 * 97);
+    assert_eq!(token.balance(&contract_id), 0);
+
+    let result = client.try_process_subscription(&sub_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotPending);
+}
+
+#[test]
+fn test_cancel_subscription_refunds_unspent_prefunding() {
+    let (env, client, buyer, farmer, _, token, _, _, _, contract_id) = setup_test();
+
+    let sub_id = client.create_subscription(&buyer, &farmer, &token.address, &100, &WEEK, &4);
+    env.ledger().set_timestamp(env.ledger().timestamp() + WEEK);
+    client.process_subscription(&sub_id);
+
+    // Farmer stops the box after two periods.
+    client.cancel_subscription(&farmer, &sub_id);
+
+    let sub = client.get_subscription(&sub_id);
+    assert_eq!(sub.status, SubscriptionStatus::Cancelled);
+    assert_eq!(sub.balance, 0);
+    assert_eq!(token.balance(&buyer), 1000 - 200);
+    // The two spawned orders stay escrowed, fee included.
+    assert_eq!(token.balance(&contract_id), 2 * 100);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + WEEK);
+    let result = client.try_process_subscription(&sub_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotPending);
+}
+
+#[test]
+fn test_cancel_subscription_non_participant_fails() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+    let stranger = Address::generate(&env);
+
+    let sub_id = client.create_subscription(&buyer, &farmer, &token.address, &100, &WEEK, &4);
+    let result = client.try_cancel_subscription(&stranger, &sub_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotOrderParticipant);
+}
+
+#[test]
+fn test_subscription_period_limit_and_missing_subscription_errors() {
+    let (_env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let result = client.try_create_subscription(&buyer, &farmer, &token.address, &1, &WEEK, &105);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::TooManyPeriods);
+
+    let result = client.try_get_subscription(&7);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::SubscriptionDoesNotExist);
+}
+
+#[test]
+fn test_pull_subscription_charges_each_period_from_allowance() {
+    let (env, client, buyer, farmer, _, token, _, _, _, contract_id) = setup_test();
+
+    token.approve(&buyer, &contract_id, &300, &(env.ledger().sequence() + 1_000));
+    let sub_id =
+        client.create_pull_subscription(&buyer, &farmer, &token.address, &100, &WEEK, &3);
+
+    let sub = client.get_subscription(&sub_id);
+    assert!(!sub.prefunded);
+    assert_eq!(sub.balance, 0);
+    assert_eq!(token.balance(&buyer), 900);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + WEEK);
+    client.process_subscription(&sub_id);
+    assert_eq!(token.balance(&buyer), 800);
+    assert_eq!(token.balance(&contract_id), 2 * 100);
+
+    // Cancelling a pull subscription has nothing to refund.
+    client.cancel_subscription(&buyer, &sub_id);
+    assert_eq!(token.balance(&buyer), 800);
+}
+
+// ── Per-order delivery deadlines ────────────────────────────────────────────
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[test]
+fn test_create_order_defaults_to_96_hour_deadline() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.delivery_deadline, env.ledger().timestamp() + 96 * 60 * 60);
+}
+
+#[test]
+fn test_refund_uses_per_order_delivery_deadline() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    // Fresh produce: a 6-hour window, well short of the old fixed 96 hours.
+    let order_id =
+        client.create_order(&buyer, &farmer, &token.address, &500, &Some(6 * 60 * 60), &None);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 6 * 60 * 60);
+    let result = client.try_refund_expired_order(&buyer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotExpired);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 1);
+    client.refund_expired_order(&buyer, &order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+}
+
+#[test]
+fn test_create_order_rejects_window_outside_bounds() {
+    let (_env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    client.set_delivery_window_bounds(&admin, &DAY, &(30 * DAY));
+    assert_eq!(client.get_delivery_window_bounds(), (DAY, 30 * DAY));
+
+    let too_short =
+        client.try_create_order(&buyer, &farmer, &token.address, &500, &Some(DAY - 1), &None);
+    assert_eq!(
+        too_short.unwrap_err().unwrap(),
+        EscrowError::InvalidDeliveryWindow
+    );
+
+    let too_long =
+        client.try_create_order(&buyer, &farmer, &token.address, &500, &Some(30 * DAY + 1), &None);
+    assert_eq!(
+        too_long.unwrap_err().unwrap(),
+        EscrowError::InvalidDeliveryWindow
+    );
+}
+
+#[test]
+fn test_set_delivery_window_bounds_rejects_admin_once_governance_set() {
+    let (env, client, _, _, _, _, _, admin, _, _) = setup_test();
+
+    let governance = env.register(MockGovernance, ());
+    client.set_governance_contract(&admin, &governance);
+
+    let result = client.try_set_delivery_window_bounds(&admin, &DAY, &(30 * DAY));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotGoverned);
+
+    client.set_delivery_window_bounds(&governance, &DAY, &(30 * DAY));
+}
+
+#[test]
+fn test_deadline_extension_requires_counterparty_approval() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &Some(2 * DAY), &None);
+    client.accept_order(&farmer, &order_id);
+    let original_deadline = client.get_order_details(&order_id).delivery_deadline;
+    let new_deadline = original_deadline + 5 * DAY;
+
+    client.request_deadline_extension(&farmer, &order_id, &new_deadline);
+    let request = client.get_deadline_extension(&order_id).unwrap();
+    assert_eq!(request.requested_by, farmer);
+
+    // The requester can't approve their own request.
+    let result = client.try_approve_deadline_extension(&farmer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotOrderParticipant);
+    assert_eq!(
+        client.get_order_details(&order_id).delivery_deadline,
+        original_deadline
+    );
+
+    client.approve_deadline_extension(&buyer, &order_id);
+    assert_eq!(client.get_order_details(&order_id).delivery_deadline, new_deadline);
+    assert_eq!(client.get_deadline_extension(&order_id), None);
+
+    // The old deadline no longer lets the buyer reclaim funds.
+    env.ledger().set_timestamp(original_deadline + 1);
+    let result = client.try_refund_expired_order(&buyer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotExpired);
+}
+
+#[test]
+fn test_deadline_extension_beyond_max_window_fails() {
+    let (_env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    let order = client.get_order_details(&order_id);
+
+    let result =
+        client.try_request_deadline_extension(&farmer, &order_id, &(order.timestamp + 91 * DAY));
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        EscrowError::InvalidDeliveryWindow
+    );
+}
+
+#[test]
+fn test_deadline_extension_bound_runs_from_acceptance() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 2 * DAY);
+    client.accept_order(&farmer, &order_id);
+    let accepted_at = env.ledger().timestamp();
+
+    // The 90-day maximum counts from acceptance, not from creation.
+    let result =
+        client.try_request_deadline_extension(&farmer, &order_id, &(accepted_at + 90 * DAY + 1));
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        EscrowError::InvalidDeliveryWindow
+    );
+    client.request_deadline_extension(&farmer, &order_id, &(accepted_at + 90 * DAY));
+    client.approve_deadline_extension(&buyer, &order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).delivery_deadline,
+        accepted_at + 90 * DAY
+    );
+}
+
+#[test]
+fn test_approve_deadline_extension_without_request_fails() {
+    let (_env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    let result = client.try_approve_deadline_extension(&buyer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NoPendingProposal);
+}
+
+#[test]
+fn test_create_split_order_records_delivery_deadline() {
+    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
+    let mut shares = Vec::new(&env);
+    shares.push_back(500i128);
+    shares.push_back(500i128);
+
+    let order_id = client.create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &shares,
+        &Some(14 * DAY),
+        &None,
+        &None,
+    );
+    let order = client.get_split_order(&order_id);
+    assert_eq!(order.delivery_deadline, order.timestamp + 14 * DAY);
+}
+
+#[test]
+fn test_split_order_delivery_window_restarts_on_activation() {
+    let (env, client, farmer, co_buyers, token, _, _admin) = setup_split_test(2);
+    env.mock_all_auths();
+    let order_id = client.create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [500i128, 500i128]),
+        &Some(2 * DAY),
+        &None,
+        &None,
+    );
+    client.fund_split_order(&co_buyers.get(0).unwrap(), &order_id);
+
+    // The last share lands six days later, well past the two-day delivery
+    // window counted from creation.
+    env.ledger().set_timestamp(env.ledger().timestamp() + 6 * DAY);
+    client.fund_split_order(&co_buyers.get(1).unwrap(), &order_id);
+    let order = client.get_split_order(&order_id);
+    assert_eq!(order.status, SplitOrderStatus::Active);
+    assert_eq!(order.delivery_deadline, env.ledger().timestamp() + 2 * DAY);
+}
+
+// ── Auto-release after buyer silence ────────────────────────────────────────
+
+#[test]
+fn test_release_unconfirmed_order_after_confirmation_window() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    let registry_id = env.register(MockRegistry, ());
+    let registry_client = MockRegistryClient::new(&env, &registry_id);
+    client.set_registry_contract(&admin, &registry_id);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.mark_delivered(&farmer, &admin, &order_id);
+    let delivered_at = client.get_order_details(&order_id).delivery_timestamp;
+
+    env.ledger().set_timestamp(delivered_at + 7 * DAY);
+    let result = client.try_release_unconfirmed_order(&order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowNotElapsed);
+
+    env.ledger().set_timestamp(delivered_at + 7 * DAY + 1);
+    client.release_unconfirmed_order(&order_id);
+
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.status, OrderStatus::Completed);
+    assert_eq!(token.balance(&farmer), 485);
+
+    let calls = registry_client.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls.get(0).unwrap().2, None);
+}
+
+#[test]
+fn test_release_unconfirmed_order_requires_delivery() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 30 * DAY);
+
+    let result = client.try_release_unconfirmed_order(&order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotDelivered);
+}
+
+#[test]
+fn test_release_unconfirmed_order_blocked_by_dispute() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.mark_delivered(&farmer, &admin, &order_id);
+    client.open_dispute(
+        &buyer,
+        &order_id,
+        &String::from_str(&env, "wrong variety"),
+        &String::from_str(&env, "QmHashAuto"),
+    );
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 30 * DAY);
+    let result = client.try_release_unconfirmed_order(&order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotPending);
+}
+
+#[test]
+fn test_release_unconfirmed_orders_skips_ineligible() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    client.set_confirmation_window(&admin, &DAY);
+    assert_eq!(client.get_confirmation_window(), DAY);
+
+    let delivered = client.create_order(&buyer, &farmer, &token.address, &200, &None, &None);
+    client.accept_order(&farmer, &delivered);
+    let undelivered = client.create_order(&buyer, &farmer, &token.address, &200, &None, &None);
+    client.accept_order(&farmer, &undelivered);
+    client.mark_delivered(&farmer, &admin, &delivered);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + DAY + 1);
+    let mut order_ids = Vec::new(&env);
+    order_ids.push_back(delivered);
+    order_ids.push_back(undelivered);
+    order_ids.push_back(999);
+    client.release_unconfirmed_orders(&order_ids);
+
+    assert_eq!(
+        client.get_order_details(&delivered).status,
+        OrderStatus::Completed
+    );
+    assert_eq!(
+        client.get_order_details(&undelivered).status,
+        OrderStatus::Pending
+    );
+    assert_eq!(token.balance(&farmer), 194);
+}
+
+// ── Escrowed platform fee ───────────────────────────────────────────────────
+
+#[test]
+fn test_partial_confirmation_forwards_pro_rata_fee() {
+    let (_env, client, buyer, farmer, collector, token, _, admin, _, contract_id) = setup_test();
+
+    // 100 kg at 10 = 1000 gross: 970 net and 30 fee held in escrow.
+    let order_id = client.create_quantity_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &100,
+        &symbol_short!("kg"),
+        &10,
+        &None,
+    );
+    client.accept_order(&farmer, &order_id);
+    client.mark_partial_delivery(&farmer, &admin, &order_id, &40);
+    client.confirm_partial_receipt(&buyer, &order_id, &40);
+    assert_eq!(token.balance(&farmer), 388);
+    assert_eq!(token.balance(&collector), 12);
+
+    client.mark_delivered(&farmer, &admin, &order_id);
+    client.confirm_receipt(&buyer, &order_id);
+    assert_eq!(token.balance(&farmer), 970);
+    assert_eq!(token.balance(&collector), 30);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_migrate_orders_rewrites_legacy_layout() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, contract_id) = setup_test();
+
+    // A pre-upgrade order: 485 net escrowed, its 15 fee already paid out.
+    token.transfer(&buyer, &contract_id, &485);
+    let legacy = LegacyOrder {
+        buyer: buyer.clone(),
+        farmer: farmer.clone(),
+        token: token.address.clone(),
+        amount: 485,
+        timestamp: env.ledger().timestamp(),
+        delivery_timestamp: 0,
+        status: OrderStatus::Pending,
+    };
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&DataKey::Order(1), &legacy);
+        env.storage().instance().set(&DataKey::OrderCount, &1u64);
+    });
+    let current = client.create_order(&buyer, &farmer, &token.address, &100, &None, &None);
+
+    let mut order_ids = Vec::new(&env);
+    order_ids.push_back(1);
+    order_ids.push_back(current);
+    order_ids.push_back(999);
+    assert_eq!(client.migrate_orders(&admin, &order_ids), 1);
+
+    let order = client.get_order_details(&1);
+    assert_eq!(order.amount, 485);
+    assert_eq!(order.fee, 0);
+    assert_eq!(order.quantity, 1);
+    assert_eq!(order.delivery_deadline, legacy.timestamp + 96 * 60 * 60);
+
+    // Cancelling refunds only what the contract actually holds.
+    client.cancel_order(&buyer, &1);
+    assert_eq!(token.balance(&buyer), 1000 - 100);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), 100);
+}
+
+#[test]
+fn test_migrate_split_orders_rewrites_legacy_layout() {
+    let (env, client, farmer, co_buyers, token, contract_id, admin) = setup_split_test(2);
+    let first = co_buyers.get(0).unwrap();
+    let second = co_buyers.get(1).unwrap();
+
+    // A pre-upgrade active split order: 485 net escrowed, its 15 fee already
+    // paid out at activation.
+    token.transfer(&first, &contract_id, &100);
+    token.transfer(&second, &contract_id, &385);
+    let mut shares = Map::new(&env);
+    shares.set(first.clone(), 100i128);
+    shares.set(second.clone(), 400i128);
+    let mut funded = Map::new(&env);
+    funded.set(first.clone(), true);
+    funded.set(second.clone(), true);
+    let legacy = LegacySplitOrder {
+        farmer: farmer.clone(),
+        token: token.address.clone(),
+        total_amount: 500,
+        co_buyers: co_buyers.clone(),
+        shares,
+        funded,
+        confirmed: Map::new(&env),
+        funded_count: 2,
+        confirmed_count: 0,
+        confirmed_value: 0,
+        net_amount: 485,
+        timestamp: env.ledger().timestamp(),
+        delivery_timestamp: 0,
+        status: SplitOrderStatus::Active,
+    };
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&DataKey::SplitOrder(1), &legacy);
+        env.storage().instance().set(&DataKey::SplitOrderCount, &1u64);
+    });
+
+    let mut order_ids = Vec::new(&env);
+    order_ids.push_back(1);
+    order_ids.push_back(999);
+    assert_eq!(client.migrate_split_orders(&admin, &order_ids), 1);
+    assert_eq!(client.migrate_split_orders(&admin, &order_ids), 0);
+
+    let order = client.get_split_order(&1);
+    assert_eq!(order.net_amount, 485);
+    assert_eq!(order.fee, 0);
+    assert_eq!(order.funding_deadline, legacy.timestamp + WEEK);
+    assert_eq!(order.delivery_deadline, legacy.timestamp + 96 * 60 * 60);
+
+    // The expiry refund returns only what the contract actually holds.
+    env.ledger().set_timestamp(order.delivery_deadline + 1);
+    client.refund_expired_split_order(&first, &1);
+    assert_eq!(token.balance(&first), 1_000 - 100 + 97);
+    assert_eq!(token.balance(&second), 1_000 - 385 + 388);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+// ── Farmer acceptance ───────────────────────────────────────────────────────
+
+#[test]
+fn test_accept_order_restarts_delivery_window() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &Some(2 * DAY), &None);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Awaiting
+    );
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + DAY);
+    client.accept_order(&farmer, &order_id);
+
+    let order = client.get_order_details(&order_id);
+    assert_eq!(order.status, OrderStatus::Pending);
+    assert_eq!(order.delivery_deadline, env.ledger().timestamp() + 2 * DAY);
+
+    let result = client.try_accept_order(&farmer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotPending);
+}
+
+#[test]
+fn test_reject_order_refunds_buyer_including_fee() {
+    let (_env, client, buyer, farmer, collector, token, _, _, _, contract_id) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let result = client.try_reject_order(&buyer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotFarmer);
+
+    client.reject_order(&farmer, &order_id);
+
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_mark_delivered_requires_acceptance() {
+    let (_env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let result = client.try_mark_delivered(&farmer, &admin, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotAccepted);
+}
+
+#[test]
+fn test_buyer_reclaims_order_after_acceptance_timeout() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &Some(30 * DAY), &None);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 2 * DAY);
+    let result = client.try_refund_expired_order(&buyer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotExpired);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 1);
+    client.refund_expired_order(&buyer, &order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+    assert_eq!(token.balance(&buyer), 1000);
+}
+
+// ── Multi-farmer carts ──────────────────────────────────────────────────────
+
+#[test]
+fn test_create_multi_order_funds_linked_child_orders() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, contract_id) = setup_test();
+    let farmer2 = Address::generate(&env);
+    let farmer3 = Address::generate(&env);
+
+    let mut lines = Vec::new(&env);
+    for (line_farmer, amount) in [(&farmer, 100i128), (&farmer2, 100), (&farmer3, 150)] {
+        lines.push_back(CartLine {
+            farmer: line_farmer.clone(),
+            token: token.address.clone(),
+            amount,
+        });
+    }
+    let cart_id = client.create_multi_order(&buyer, &lines, &None);
+
+    let cart = client.get_cart(&cart_id);
+    assert_eq!(cart.buyer, buyer);
+    assert_eq!(cart.order_ids.len(), 3);
+    assert_eq!(token.balance(&buyer), 650);
+    assert_eq!(token.balance(&contract_id), 350);
+
+    // 3% of the 350 cart total is 10, apportioned 2 / 2 / 6 instead of the
+    // 3 / 3 / 4 that per-line rounding would give.
+    let mut total_fee = 0;
+    for order_id in cart.order_ids.iter() {
+        let order = client.get_order_details(&order_id);
+        assert_eq!(order.cart_id, Some(cart_id));
+        assert_eq!(order.buyer, buyer);
+        total_fee += order.fee;
+    }
+    assert_eq!(total_fee, 10);
+
+    // Lines settle independently.
+    let first = cart.order_ids.get(0).unwrap();
+    client.accept_order(&farmer, &first);
+    client.confirm_receipt(&buyer, &first);
+    assert_eq!(token.balance(&farmer), 98);
+    assert_eq!(token.balance(&collector), 2);
+
+    let second = cart.order_ids.get(1).unwrap();
+    client.accept_order(&farmer2, &second);
+    client.open_dispute(
+        &buyer,
+        &second,
+        &String::from_str(&env, "bruised"),
+        &String::from_str(&env, "QmCart"),
+    );
+    client.resolve_dispute(&admin, &second, &DisputeResolution::Refund);
+    finalize_ruling(&env, &client, second);
+    assert_eq!(token.balance(&buyer), 750);
+}
+
+#[test]
+fn test_create_multi_order_rejects_bad_line_atomically() {
+    let (env, client, buyer, farmer, _, token, _, _, _, contract_id) = setup_test();
+
+    let mut lines = Vec::new(&env);
+    lines.push_back(CartLine {
+        farmer: farmer.clone(),
+        token: token.address.clone(),
+        amount: 100,
+    });
+    lines.push_back(CartLine {
+        farmer: farmer.clone(),
+        token: token.address.clone(),
+        amount: 0,
+    });
+
+    let result = client.try_create_multi_order(&buyer, &lines, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountMustBePositive);
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(client.get_order_count(), 0);
+}
+
+#[test]
+fn test_create_multi_order_rejects_empty_and_oversized_carts() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+
+    let mut lines = Vec::new(&env);
+    let result = client.try_create_multi_order(&buyer, &lines, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidCartSize);
+
+    for _ in 0..21 {
+        lines.push_back(CartLine {
+            farmer: farmer.clone(),
+            token: token.address.clone(),
+            amount: 10,
+        });
+    }
+    let result = client.try_create_multi_order(&buyer, &lines, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidCartSize);
+    assert_eq!(client.get_order_count(), 0);
+}
+
+// ── Dispute evidence log ────────────────────────────────────────────────────
+
+fn open_test_dispute(
+    env: &Env,
+    client: &EscrowContractClient,
+    buyer: &Address,
+    farmer: &Address,
+    token: &Address,
+) -> u64 {
+    let order_id = client.create_order(buyer, farmer, token, &500, &None, &None);
+    client.accept_order(farmer, &order_id);
+    client.open_dispute(
+        buyer,
+        &order_id,
+        &String::from_str(env, "crates arrived empty"),
+        &String::from_str(env, "QmBuyerPhotos"),
+    );
+    order_id
+}
+
+/// Lets a first-round dispute ruling's appeal window lapse unappealed and
+/// executes it.
+fn finalize_ruling(env: &Env, client: &EscrowContractClient, order_id: u64) {
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    client.finalize_dispute(&order_id);
+}
+
+/// Configures an arbitrator pool staked in `stake_token` and stakes `count`
+/// fresh arbitrators at the 100 minimum.
+fn stake_test_arbitrators(
+    env: &Env,
+    client: &EscrowContractClient,
+    admin: &Address,
+    stake_token: &Address,
+    count: u32,
+    panel_size: u32,
+    quorum: u32,
+) -> Vec<Address> {
+    client.set_arbitration_config(admin, stake_token, &100, &panel_size, &quorum);
+    let mut arbitrators = Vec::new(env);
+    for _ in 0..count {
+        let arbitrator = Address::generate(env);
+        token::StellarAssetClient::new(env, stake_token).mint(&arbitrator, &100);
+        client.stake_arbitrator(&arbitrator, &100);
+        arbitrators.push_back(arbitrator);
+    }
+    arbitrators
+}
+
+#[test]
+fn test_dispute_evidence_log_records_both_sides() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 60);
+    client.submit_dispute_evidence(
+        &farmer,
+        &order_id,
+        &String::from_str(&env, "QmFarmerWaybill"),
+    );
+
+    let evidence = client.get_dispute_evidence(&order_id);
+    assert_eq!(evidence.len(), 2);
+    let opening = evidence.get(0).unwrap();
+    assert_eq!(opening.submitted_by, buyer);
+    assert_eq!(opening.evidence_hash, String::from_str(&env, "QmBuyerPhotos"));
+    let response = evidence.get(1).unwrap();
+    assert_eq!(response.submitted_by, farmer);
+    assert_eq!(response.timestamp, env.ledger().timestamp());
+
+    let stranger = Address::generate(&env);
+    let result =
+        client.try_submit_dispute_evidence(&stranger, &order_id, &String::from_str(&env, "Qm"));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotOrderParticipant);
+}
+
+#[test]
+fn test_submit_dispute_evidence_after_window_fails() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    let result =
+        client.try_submit_dispute_evidence(&farmer, &order_id, &String::from_str(&env, "QmLate"));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowClosed);
+}
+
+#[test]
+fn test_dispute_evidence_quota_is_per_party() {
+    let (env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+
+    // The opening submission counts towards the buyer's quota of 10.
+    for _ in 0..9 {
+        client.submit_dispute_evidence(&buyer, &order_id, &String::from_str(&env, "QmMore"));
+    }
+    let result =
+        client.try_submit_dispute_evidence(&buyer, &order_id, &String::from_str(&env, "QmExtra"));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::EvidenceLimitReached);
+
+    client.submit_dispute_evidence(
+        &farmer,
+        &order_id,
+        &String::from_str(&env, "QmFarmerWaybill"),
+    );
+    assert_eq!(client.get_dispute_evidence(&order_id).len(), 11);
+}
+
+#[test]
+fn test_arbitrator_voting_waits_for_evidence_window() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    let arbitrator = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 1, 1, 1)
+        .get(0)
+        .unwrap();
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    let result = client.try_vote_to_resolve(&arbitrator, &order_id, &DisputeResolution::Refund);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowNotElapsed);
+
+    // The farmer's response closes the window early.
+    client.submit_dispute_evidence(
+        &farmer,
+        &order_id,
+        &String::from_str(&env, "QmFarmerWaybill"),
+    );
+    client.vote_to_resolve(&arbitrator, &order_id, &DisputeResolution::Refund);
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+}
+
+#[test]
+fn test_arbitrator_voting_opens_when_evidence_window_elapses() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    let arbitrator = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 1, 1, 1)
+        .get(0)
+        .unwrap();
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+
+    client.vote_to_resolve(&arbitrator, &order_id, &DisputeResolution::Release);
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Completed
+    );
+}
+
+// ── Dispute bonds ───────────────────────────────────────────────────────────
+
+#[test]
+fn test_dispute_bond_returned_when_opener_wins() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+    client.set_dispute_bond_bps(&admin, &1_000);
+    assert_eq!(client.get_dispute_bond_bps(), 1_000);
+
+    // 10% of the 485 escrowed is a 48 bond.
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    assert_eq!(client.get_dispute_bond(&order_id), 48);
+    assert_eq!(token.balance(&buyer), 1000 - 500 - 48);
+
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(client.get_dispute_bond(&order_id), 0);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_dispute_bond_forfeited_to_counterparty_and_majority_arbitrators() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, contract_id) = setup_test();
+    client.set_dispute_bond_bps(&admin, &1_000);
+    let arbitrators = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 3, 3, 2);
+    let majority_voter = arbitrators.get(0).unwrap();
+    let second_voter = arbitrators.get(1).unwrap();
+    let dissenter = arbitrators.get(2).unwrap();
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    client.vote_to_resolve(&dissenter, &order_id, &DisputeResolution::Refund);
+    client.vote_to_resolve(&majority_voter, &order_id, &DisputeResolution::Release);
+    client.vote_to_resolve(&second_voter, &order_id, &DisputeResolution::Release);
+    finalize_ruling(&env, &client, order_id);
+
+    // The buyer loses: 24 of the 48 bond to the farmer, 12 to each arbitrator
+    // whose vote matched the outcome.
+    assert_eq!(token.balance(&buyer), 1000 - 500 - 48);
+    assert_eq!(token.balance(&farmer), 485 + 24);
+    assert_eq!(token.balance(&majority_voter), 12);
+    assert_eq!(token.balance(&second_voter), 12);
+    assert_eq!(token.balance(&dissenter), 0);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_dispute_bond_apportioned_on_split_resolution() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    client.set_dispute_bond_bps(&admin, &1_000);
+    token::StellarAssetClient::new(&env, &token.address).mint(&farmer, &1000);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.open_dispute(
+        &farmer,
+        &order_id,
+        &String::from_str(&env, "buyer refuses delivery"),
+        &String::from_str(&env, "QmFarmer"),
+    );
+    assert_eq!(token.balance(&farmer), 1000 - 48);
+
+    // 75% to the buyer: the farmer opener keeps 25% of the bond (12); the
+    // other 36 goes to the buyer.
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Split(7_500));
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(token.balance(&farmer), 1000 - 48 + 12 + (485 - 363));
+    assert_eq!(token.balance(&buyer), 500 + 363 + 11 + 36);
+}
+
+// ── Majority arbitrator voting ──────────────────────────────────────────────
+
+fn setup_arbitration_test(
+    arbitrator_count: u32,
+    quorum: u32,
+) -> (
+    Env,
+    EscrowContractClient<'static>,
+    Address,
+    Address,
+    token::Client<'static>,
+    Address,
+    Vec<Address>,
+    u64,
+) {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    let arbitrators = stake_test_arbitrators(
+        &env,
+        &client,
+        &admin,
+        &usdc.address,
+        arbitrator_count,
+        arbitrator_count,
+        quorum,
+    );
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    (env, client, buyer, farmer, token, admin, arbitrators, order_id)
+}
+
+#[test]
+fn test_vote_to_resolve_waits_for_single_outcome_quorum() {
+    let (env, client, _, _, _, _, arbitrators, order_id) = setup_arbitration_test(3, 2);
+
+    client.vote_to_resolve(
+        &arbitrators.get(0).unwrap(),
+        &order_id,
+        &DisputeResolution::Refund,
+    );
+    client.vote_to_resolve(
+        &arbitrators.get(1).unwrap(),
+        &order_id,
+        &DisputeResolution::Release,
+    );
+    // Two votes cast, but no outcome has two of them yet.
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Disputed
+    );
+
+    client.vote_to_resolve(
+        &arbitrators.get(2).unwrap(),
+        &order_id,
+        &DisputeResolution::Refund,
+    );
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+}
+
+#[test]
+fn test_split_votes_resolve_at_average_ratio() {
+    let (env, client, buyer, farmer, token, _, arbitrators, order_id) =
+        setup_arbitration_test(2, 2);
+
+    client.vote_to_resolve(
+        &arbitrators.get(0).unwrap(),
+        &order_id,
+        &DisputeResolution::Split(4_000),
+    );
+    client.vote_to_resolve(
+        &arbitrators.get(1).unwrap(),
+        &order_id,
+        &DisputeResolution::Split(6_000),
+    );
+    finalize_ruling(&env, &client, order_id);
+
+    // Averaged to 50/50 of the 485 escrowed and 15 fee.
+    assert_eq!(token.balance(&buyer), 500 + 242 + 7);
+    assert_eq!(token.balance(&farmer), 243);
+}
+
+#[test]
+fn test_vote_after_voting_deadline_fails() {
+    let (env, client, _, _, _, _, arbitrators, order_id) = setup_arbitration_test(3, 2);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 7 * DAY);
+    let result = client.try_vote_to_resolve(
+        &arbitrators.get(0).unwrap(),
+        &order_id,
+        &DisputeResolution::Refund,
+    );
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowClosed);
+}
+
+#[test]
+fn test_admin_resolves_only_after_deadline_or_deadlock() {
+    let (env, client, _, _, _, admin, arbitrators, order_id) = setup_arbitration_test(2, 2);
+
+    let result = client.try_resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowNotElapsed);
+
+    // A 1-1 split of a 2-of-2 pool can never reach quorum.
+    client.vote_to_resolve(
+        &arbitrators.get(0).unwrap(),
+        &order_id,
+        &DisputeResolution::Refund,
+    );
+    client.vote_to_resolve(
+        &arbitrators.get(1).unwrap(),
+        &order_id,
+        &DisputeResolution::Release,
+    );
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Split(5_000));
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Completed
+    );
+}
+
+#[test]
+fn test_admin_resolves_after_voting_deadline() {
+    let (env, client, _, _, _, admin, _, order_id) = setup_arbitration_test(3, 2);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 7 * DAY + 1);
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Completed
+    );
+}
+
+// ── Staked arbitrator pool ──────────────────────────────────────────────────
+
+#[test]
+fn test_set_arbitration_config_rejects_out_of_range_parameters() {
+    let (_, client, _, _, _, _, usdc, admin, _, _) = setup_test();
+    for (min_stake, panel_size, quorum) in [(0, 3, 2), (100, 10, 2), (100, 3, 0), (100, 3, 4)] {
+        let result = client.try_set_arbitration_config(
+            &admin,
+            &usdc.address,
+            &min_stake,
+            &panel_size,
+            &quorum,
+        );
+        assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidArbitrationConfig);
+    }
+}
+
+#[test]
+fn test_arbitrator_joins_pool_once_min_stake_is_met() {
+    let (env, client, _, _, _, _, usdc, admin, _, contract_id) = setup_test();
+    client.set_arbitration_config(&admin, &usdc.address, &100, &3, &2);
+    let arbitrator = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&arbitrator, &150);
+
+    client.stake_arbitrator(&arbitrator, &60);
+    assert_eq!(client.get_arbitrators().len(), 0);
+
+    client.stake_arbitrator(&arbitrator, &60);
+    assert_eq!(
+        client.get_arbitrators(),
+        Vec::from_array(&env, [arbitrator.clone()])
+    );
+    assert_eq!(client.get_arbitrator_stake(&arbitrator).amount, 120);
+    assert_eq!(usdc.balance(&contract_id), 120);
+    assert_eq!(client.get_quorum(), 2);
+
+    client.unstake_arbitrator(&arbitrator, &30);
+    assert_eq!(client.get_arbitrators().len(), 0);
+    assert_eq!(usdc.balance(&arbitrator), 60);
+}
+
+#[test]
+fn test_dispute_panel_is_drawn_from_pool_excluding_order_parties() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    let arbitrators = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 4, 3, 2);
+    // The farmer is staked too, but can't sit on a panel for their own order.
+    token::StellarAssetClient::new(&env, &usdc.address).mint(&farmer, &100);
+    client.stake_arbitrator(&farmer, &100);
+    assert_eq!(client.get_arbitrators().len(), 5);
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    let panel = client.get_dispute_panel(&order_id);
+    assert_eq!(panel.len(), 3);
+    assert!(!panel.contains(&farmer));
+    for member in panel.iter() {
+        assert!(arbitrators.contains(&member));
+        assert_eq!(client.get_arbitrator_stake(&member).open_panels, 1);
+    }
+
+    let outsider = arbitrators.iter().find(|a| !panel.contains(a)).unwrap();
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    let result = client.try_vote_to_resolve(&outsider, &order_id, &DisputeResolution::Refund);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ArbitratorNotFound);
+}
+
+#[test]
+fn test_dispute_without_enough_eligible_arbitrators_falls_back_to_admin() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    stake_test_arbitrators(&env, &client, &admin, &usdc.address, 1, 3, 2);
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    assert_eq!(client.get_dispute_panel(&order_id).len(), 0);
+
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+}
+
+#[test]
+fn test_unstake_below_min_blocked_while_on_open_panel() {
+    let (_env, client, _, _, _, _, arbitrators, order_id) = setup_arbitration_test(1, 1);
+    let arbitrator = arbitrators.get(0).unwrap();
+
+    let result = client.try_unstake_arbitrator(&arbitrator, &1);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InsufficientStake);
+
+    client.vote_to_resolve(&arbitrator, &order_id, &DisputeResolution::Release);
+    assert_eq!(client.get_arbitrator_stake(&arbitrator).open_panels, 0);
+    client.unstake_arbitrator(&arbitrator, &100);
+    assert_eq!(client.get_arbitrators().len(), 0);
+
+    let result = client.try_unstake_arbitrator(&arbitrator, &1);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InsufficientStake);
+}
+
+#[test]
+fn test_panel_member_missing_voting_deadline_is_slashed() {
+    let (env, client, buyer, farmer, fee_collector, token, usdc, admin, _, _) = setup_test();
+    let arbitrators = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 2, 2, 2);
+    let voter = arbitrators.get(0).unwrap();
+    let absentee = arbitrators.get(1).unwrap();
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    client.vote_to_resolve(&voter, &order_id, &DisputeResolution::Release);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + WEEK);
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+
+    // 10% of the absentee's stake goes to the fee collector, leaving them
+    // under the minimum and out of the pool.
+    assert_eq!(client.get_arbitrator_stake(&absentee).amount, 90);
+    assert_eq!(client.get_arbitrator_stake(&voter).amount, 100);
+    assert_eq!(usdc.balance(&fee_collector), 10);
+    assert_eq!(client.get_arbitrators(), Vec::from_array(&env, [voter]));
+}
+
+#[test]
+fn test_repeated_dissent_against_consensus_is_slashed() {
+    let (env, client, buyer, farmer, fee_collector, token, usdc, admin, _, _) = setup_test();
+    token::StellarAssetClient::new(&env, &token.address).mint(&buyer, &1000);
+    let arbitrators = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 3, 3, 2);
+    let dissenter = arbitrators.get(2).unwrap();
+
+    for round in 1..=3 {
+        let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+        env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+        client.vote_to_resolve(&dissenter, &order_id, &DisputeResolution::Refund);
+        for voter in arbitrators.slice(0..2).iter() {
+            client.vote_to_resolve(&voter, &order_id, &DisputeResolution::Release);
+        }
+        if round < 3 {
+            assert_eq!(client.get_arbitrator_stake(&dissenter).dissents, round);
+        }
+    }
+
+    let stake = client.get_arbitrator_stake(&dissenter);
+    assert_eq!(stake.amount, 90);
+    assert_eq!(stake.dissents, 0);
+    assert_eq!(usdc.balance(&fee_collector), 10);
+    let agreeing = arbitrators.get(0).unwrap();
+    assert_eq!(client.get_arbitrator_stake(&agreeing).amount, 100);
+}
+
+// ── Dispute appeals ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_first_round_ruling_held_until_appeal_window_lapses() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    assert_eq!(client.get_dispute_round(&order_id), 1);
+
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    let ruling = client.get_dispute_ruling(&order_id).unwrap();
+    assert_eq!(ruling.resolution, DisputeResolution::Refund);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Disputed
+    );
+    assert_eq!(token.balance(&buyer), 500);
+
+    let result = client.try_finalize_dispute(&order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowNotElapsed);
+    let result = client.try_resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowClosed);
+
+    finalize_ruling(&env, &client, order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(client.get_dispute_ruling(&order_id), None);
+}
+
+#[test]
+fn test_successful_appeal_to_larger_panel_returns_appeal_bond() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    stake_test_arbitrators(&env, &client, &admin, &usdc.address, 4, 1, 1);
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    let first_panel = client.get_dispute_panel(&order_id);
+    assert_eq!(first_panel.len(), 1);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    let first_arbitrator = first_panel.get(0).unwrap();
+    client.vote_to_resolve(&first_arbitrator, &order_id, &DisputeResolution::Release);
+    assert_eq!(token.balance(&farmer), 0);
+
+    // 10% of the 485 escrowed is a 48 appeal bond.
+    client.appeal_dispute(&buyer, &order_id);
+    assert_eq!(client.get_dispute_round(&order_id), 2);
+    assert_eq!(client.get_dispute_appeal(&order_id).unwrap().bond, 48);
+    assert_eq!(token.balance(&buyer), 1000 - 500 - 48);
+
+    let appeal_panel = client.get_dispute_panel(&order_id);
+    assert_eq!(appeal_panel.len(), 3);
+    assert!(!appeal_panel.contains(&first_arbitrator));
+
+    for arbitrator in appeal_panel.slice(0..2).iter() {
+        client.vote_to_resolve(&arbitrator, &order_id, &DisputeResolution::Refund);
+    }
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Refunded
+    );
+    assert_eq!(token.balance(&buyer), 1000);
+    assert_eq!(token.balance(&farmer), 0);
+}
+
+#[test]
+fn test_failed_appeal_forfeits_bond_to_counterparty_via_governance_fallback() {
+    let (env, client, buyer, farmer, fee_collector, token, _, admin, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    client.appeal_dispute(&buyer, &order_id);
+    assert_eq!(client.get_dispute_panel(&order_id).len(), 0);
+
+    // No pool to escalate to, so the final round falls to governance, here
+    // still the admin.
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Completed
+    );
+    assert_eq!(token.balance(&farmer), 485 + 48);
+    assert_eq!(token.balance(&fee_collector), 15);
+    assert_eq!(token.balance(&buyer), 1000 - 500 - 48);
+}
+
+#[test]
+fn test_appeal_rejected_outside_window_or_from_non_party() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+
+    let result = client.try_appeal_dispute(&farmer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NoPendingProposal);
+
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    let stranger = Address::generate(&env);
+    let result = client.try_appeal_dispute(&stranger, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotOrderParticipant);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    let result = client.try_appeal_dispute(&farmer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WindowClosed);
+}
+
+// ── Bilateral settlement ────────────────────────────────────────────────────
+
+#[test]
+fn test_accepted_settlement_splits_pending_order() {
+    let (_env, client, buyer, farmer, fee_collector, token, _, _, _, _) = setup_test();
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+
+    client.propose_settlement(&buyer, &order_id, &2_000);
+    assert_eq!(
+        client.get_settlement_proposal(&order_id).unwrap().buyer_refund_bps,
+        2_000
+    );
+    let result = client.try_accept_settlement(&buyer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotOrderParticipant);
+
+    // 20% of the 485 escrowed (97) and of the 15 fee (3) go back to the buyer.
+    client.accept_settlement(&farmer, &order_id);
+    assert_eq!(
+        client.get_order_details(&order_id).status,
+        OrderStatus::Completed
+    );
+    assert_eq!(token.balance(&buyer), 500 + 100);
+    assert_eq!(token.balance(&farmer), 388);
+    assert_eq!(token.balance(&fee_collector), 12);
+    assert_eq!(client.get_settlement_proposal(&order_id), None);
+}
+
+#[test]
+fn test_settlement_withdraws_dispute_and_reports_agreed_split() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    let registry_id = env.register(MockRegistry, ());
+    let registry_client = MockRegistryClient::new(&env, &registry_id);
+    client.set_registry_contract(&admin, &registry_id);
+    client.set_dispute_bond_bps(&admin, &1_000);
+
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    client.propose_settlement(&farmer, &order_id, &5_000);
+    client.accept_settlement(&buyer, &order_id);
+
+    // Half of 485 + 15 back to the buyer, and the buyer's 48 bond split
+    // evenly with the farmer as the agreed outcome dictates.
+    assert!(client.get_dispute(&order_id).resolved);
+    assert_eq!(token.balance(&buyer), 1000 - 500 - 48 + 242 + 7 + 24);
+    assert_eq!(token.balance(&farmer), 485 - 242 + 24);
+    let result = client.try_resolve_dispute(&admin, &order_id, &DisputeResolution::Refund);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotDisputed);
+
+    let calls = registry_client.calls();
+    let (_, _, reported_outcome) = calls.get(calls.len() - 1).unwrap();
+    assert_eq!(reported_outcome, Some(5_000));
+}
+
+#[test]
+fn test_settlement_during_appeal_clears_appeal_round() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+    client.appeal_dispute(&buyer, &order_id);
+    assert_eq!(client.get_dispute_round(&order_id), 2);
+
+    client.propose_settlement(&farmer, &order_id, &10_000);
+    client.accept_settlement(&buyer, &order_id);
+
+    assert_eq!(client.get_dispute_appeal(&order_id), None);
+    assert_eq!(client.get_dispute_round(&order_id), 1);
+    assert_eq!(token.balance(&buyer), 1000);
+}
+
+#[test]
+fn test_settlement_rejected_before_acceptance_or_without_proposal() {
+    let (_env, client, buyer, farmer, _, token, _, _, _, _) = setup_test();
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let result = client.try_propose_settlement(&buyer, &order_id, &2_000);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::OrderNotPending);
+
+    client.accept_order(&farmer, &order_id);
+    let result = client.try_propose_settlement(&buyer, &order_id, &10_001);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidSplitRatio);
+    let result = client.try_accept_settlement(&farmer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NoPendingProposal);
+}
+
+// ---------------------------------------------------------------------------
+// Idle-balance yield vault
+// ---------------------------------------------------------------------------
+
+/// Stand-in lending pool tracking positions per `(owner, token)`. `accrue`
+/// credits interest to a position; tests mint the backing tokens separately.
+#[contract]
+struct MockVault;
+
+#[contractimpl]
+impl MockVault {
+    pub fn deposit(env: Env, from: Address, token: Address, amount: i128) {
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        Self::accrue(env, from, token, amount);
+    }
+
+    pub fn withdraw(env: Env, to: Address, token: Address, amount: i128) {
+        to.require_auth();
+        let position = Self::balance(env.clone(), to.clone(), token.clone());
+        assert!(amount <= position, "insufficient position");
+        env.storage()
+            .instance()
+            .set(&(to.clone(), token.clone()), &(position - amount));
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+    }
+
+    pub fn balance(env: Env, owner: Address, token: Address) -> i128 {
+        env.storage().instance().get(&(owner, token)).unwrap_or(0)
+    }
+
+    pub fn accrue(env: Env, owner: Address, token: Address, amount: i128) {
+        let position = Self::balance(env.clone(), owner.clone(), token.clone());
+        env.storage()
+            .instance()
+            .set(&(owner, token), &(position + amount));
+    }
+}
+
+#[test]
+fn test_yield_vault_sweeps_recalls_and_shares_yield_with_buyer() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, contract_id) = setup_test();
+    let vault_id = env.register(MockVault, ());
+    let vault = MockVaultClient::new(&env, &vault_id);
+    client.set_yield_config(&admin, &vault_id, &2_000, &5_000);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+
+    // 20% of the 500 held stays liquid.
+    assert_eq!(client.sweep_idle_funds(&token.address), 400);
+    assert_eq!(token.balance(&contract_id), 100);
+    assert_eq!(client.get_vault_position(&token.address).principal, 400);
+
+    vault.accrue(&contract_id, &token.address, &40);
+    token::StellarAssetClient::new(&env, &token.address).mint(&vault_id, &40);
+    assert_eq!(client.harvest_yield(&token.address), 40);
+    assert_eq!(token.balance(&collector), 20);
+    assert_eq!(client.get_vault_position(&token.address).buyer_pool, 20);
+
+    // Paying out more than is liquid recalls the difference from the vault.
+    client.confirm_receipt(&buyer, &order_id);
+    assert_eq!(token.balance(&farmer), 485);
+    assert_eq!(token.balance(&collector), 35);
+
+    assert_eq!(client.get_claimable_yield(&buyer, &token.address), 20);
+    assert_eq!(client.claim_yield(&buyer, &token.address), 20);
+    assert_eq!(token.balance(&buyer), 520);
+    assert_eq!(client.get_claimable_yield(&buyer, &token.address), 0);
+    assert_eq!(vault.balance(&contract_id, &token.address), 0);
+    assert_eq!(token.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_yield_is_shared_over_open_order_escrow_only() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, contract_id) = setup_test();
+    // Escrow the vault never sees: an order from before the vault was
+    // enabled and an arbitrator stake in the same token.
+    let early_order = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &early_order);
+    stake_test_arbitrators(&env, &client, &admin, &token.address, 1, 1, 1);
+
+    let vault_id = env.register(MockVault, ());
+    client.set_yield_config(&admin, &vault_id, &0, &5_000);
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+
+    assert_eq!(client.sweep_idle_funds(&token.address), 500);
+    assert_eq!(token.balance(&contract_id), 500 + 100);
+    assert_eq!(client.get_vault_position(&token.address).order_escrow, 500);
+
+    MockVaultClient::new(&env, &vault_id).accrue(&contract_id, &token.address, &40);
+    token::StellarAssetClient::new(&env, &token.address).mint(&vault_id, &40);
+    client.harvest_yield(&token.address);
+
+    // The whole buyer share goes to the one order entitled to it, leaving
+    // nothing stranded in the pool.
+    client.confirm_receipt(&buyer, &order_id);
+    client.confirm_receipt(&buyer, &early_order);
+    assert_eq!(client.get_claimable_yield(&buyer, &token.address), 20);
+    let position = client.get_vault_position(&token.address);
+    assert_eq!(position.buyer_pool, 0);
+    assert_eq!(position.order_escrow, 0);
+}
+
+#[test]
+fn test_disable_yield_withdraws_everything_from_vault() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, contract_id) = setup_test();
+    assert_eq!(
+        client.try_sweep_idle_funds(&token.address).unwrap_err().unwrap(),
+        EscrowError::VaultNotConfigured
+    );
+    let vault_id = env.register(MockVault, ());
+    assert_eq!(
+        client
+            .try_set_yield_config(&admin, &vault_id, &10_001, &0)
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::InvalidSplitRatio
+    );
+    client.set_yield_config(&admin, &vault_id, &0, &0);
+
+    client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    assert_eq!(client.sweep_idle_funds(&token.address), 500);
+    MockVaultClient::new(&env, &vault_id).accrue(&contract_id, &token.address, &10);
+    token::StellarAssetClient::new(&env, &token.address).mint(&vault_id, &10);
+
+    client.disable_yield(&admin);
+    assert_eq!(client.get_yield_config(), None);
+    assert_eq!(token.balance(&contract_id), 500);
+    assert_eq!(token.balance(&collector), 10);
+    assert_eq!(client.get_vault_position(&token.address).principal, 0);
+}
+
+// ---------------------------------------------------------------------------
+// Tiered and per-farmer fee schedules
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_token_fee_schedule_applies_tiers_and_bounds() {
+    let (env, client, buyer, farmer, _, token, usdc, admin, _, _) = setup_test();
+    let schedule = TokenFeeSchedule {
+        rate_bps: 300,
+        tiers: Vec::from_array(
+            &env,
+            [
+                FeeTier { min_amount: 400, rate_bps: 200 },
+                FeeTier { min_amount: 800, rate_bps: 100 },
+            ],
+        ),
+        min_fee: 5,
+        max_fee: Some(20),
+    };
+    client.set_token_fee_schedule(&admin, &token.address, &Some(schedule.clone()));
+    assert_eq!(client.get_token_fee_schedule(&token.address), Some(schedule.clone()));
+
+    assert_eq!(client.quote_fee(&farmer, &token.address, &3), 3);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &100), 5);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &500), 10);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &900), 9);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &3_000), 20);
+    // Other tokens keep the flat rate.
+    assert_eq!(client.quote_fee(&farmer, &usdc.address, &500), 15);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let order = client.get_order_details(&order_id);
+    assert_eq!((order.fee, order.amount), (10, 490));
+
+    let mut unordered = schedule.clone();
+    unordered.tiers = Vec::from_array(
+        &env,
+        [
+            FeeTier { min_amount: 800, rate_bps: 100 },
+            FeeTier { min_amount: 400, rate_bps: 200 },
+        ],
+    );
+    assert_eq!(
+        client
+            .try_set_token_fee_schedule(&admin, &token.address, &Some(unordered))
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::InvalidFeeSchedule
+    );
+    let mut inverted = schedule.clone();
+    inverted.max_fee = Some(4);
+    assert_eq!(
+        client
+            .try_set_token_fee_schedule(&admin, &token.address, &Some(inverted))
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::InvalidFeeSchedule
+    );
+    let mut too_high = schedule;
+    too_high.rate_bps = 10_001;
+    assert_eq!(
+        client
+            .try_set_token_fee_schedule(&admin, &token.address, &Some(too_high))
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::FeeRateTooHigh
+    );
+
+    client.set_token_fee_schedule(&admin, &token.address, &None);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &500), 15);
+}
+
+#[test]
+fn test_reputation_discount_reduces_farmer_fee() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    client.set_reputation_discounts(
+        &admin,
+        &Vec::from_array(
+            &env,
+            [
+                ReputationDiscount { min_score: 100, discount_bps: 5_000 },
+                ReputationDiscount { min_score: 200, discount_bps: 10_000 },
+            ],
+        ),
+    );
+    // Discounts need a registry to read reputation from.
+    assert_eq!(client.quote_fee(&farmer, &token.address, &500), 15);
+
+    let registry_id = env.register(MockRegistry, ());
+    let registry = MockRegistryClient::new(&env, &registry_id);
+    client.set_registry_contract(&admin, &registry_id);
+    registry.set_score(&farmer, &150);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &500), 8);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    let order = client.get_order_details(&order_id);
+    assert_eq!((order.fee, order.amount), (8, 492));
+
+    registry.set_score(&farmer, &250);
+    assert_eq!(client.quote_fee(&farmer, &token.address, &500), 0);
+
+    let too_high = Vec::from_array(
+        &env,
+        [ReputationDiscount { min_score: 0, discount_bps: 10_001 }],
+    );
+    assert_eq!(
+        client
+            .try_set_reputation_discounts(&admin, &too_high)
+            .unwrap_err()
+            .unwrap(),
+        EscrowError::FeeRateTooHigh
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Fee revenue splitting
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_fee_split_accrues_claimable_balances() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, _) = setup_test();
+    let treasury = Address::generate(&env);
+    let insurance = Address::generate(&env);
+    let partner = Address::generate(&env);
+    let share = |beneficiary: &Address, bps: u32| FeeShare {
+        beneficiary: beneficiary.clone(),
+        bps,
+    };
+
+    let short = Vec::from_array(&env, [share(&treasury, 7_000), share(&insurance, 2_000)]);
+    assert_eq!(
+        client.try_set_fee_split(&admin, &short).unwrap_err().unwrap(),
+        EscrowError::InvalidSplitRatio
+    );
+    let duplicate = Vec::from_array(&env, [share(&treasury, 5_000), share(&treasury, 5_000)]);
+    assert_eq!(
+        client.try_set_fee_split(&admin, &duplicate).unwrap_err().unwrap(),
+        EscrowError::InvalidSplitRatio
+    );
+
+    client.set_fee_split(
+        &admin,
+        &Vec::from_array(
+            &env,
+            [
+                share(&treasury, 7_000),
+                share(&insurance, 2_000),
+                share(&partner, 1_000),
+            ],
+        ),
+    );
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.confirm_receipt(&buyer, &order_id);
+
+    // 15 split 10.5 / 3 / 1.5; the rounding dust goes to the treasury.
+    assert_eq!(token.balance(&collector), 0);
+    assert_eq!(client.get_fee_balance(&treasury, &token.address), 11);
+    assert_eq!(client.get_fee_balance(&insurance, &token.address), 3);
+    assert_eq!(client.get_fee_balance(&partner, &token.address), 1);
+
+    assert_eq!(client.claim_fees(&treasury, &token.address), 11);
+    assert_eq!(token.balance(&treasury), 11);
+    assert_eq!(client.get_fee_balance(&treasury, &token.address), 0);
+    assert_eq!(client.claim_fees(&treasury, &token.address), 0);
+
+    // Clearing the split sends fees straight to the collector again.
+    client.set_fee_split(&admin, &Vec::new(&env));
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &400, &None, &None);
+    client.accept_order(&farmer, &order_id);
+    client.confirm_receipt(&buyer, &order_id);
+    assert_eq!(token.balance(&collector), 12);
+}
+
+#[test]
+fn test_fee_split_covers_vault_yield_and_slashed_stakes() {
+    let (env, client, buyer, farmer, collector, token, usdc, admin, _, contract_id) = setup_test();
+    let treasury = Address::generate(&env);
+    client.set_fee_split(
+        &admin,
+        &Vec::from_array(
+            &env,
+            [FeeShare {
+                beneficiary: treasury.clone(),
+                bps: 10_000,
+            }],
+        ),
+    );
+
+    let vault_id = env.register(MockVault, ());
+    client.set_yield_config(&admin, &vault_id, &0, &5_000);
+    let arbitrators = stake_test_arbitrators(&env, &client, &admin, &usdc.address, 2, 2, 2);
+    let order_id = open_test_dispute(&env, &client, &buyer, &farmer, &token.address);
+    client.sweep_idle_funds(&token.address);
+    MockVaultClient::new(&env, &vault_id).accrue(&contract_id, &token.address, &40);
+    token::StellarAssetClient::new(&env, &token.address).mint(&vault_id, &40);
+    client.harvest_yield(&token.address);
+    assert_eq!(client.get_fee_balance(&treasury, &token.address), 20);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3 * DAY + 1);
+    let voter = arbitrators.get(0).unwrap();
+    client.vote_to_resolve(&voter, &order_id, &DisputeResolution::Release);
+    env.ledger().set_timestamp(env.ledger().timestamp() + WEEK);
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Release);
+
+    assert_eq!(client.get_fee_balance(&treasury, &usdc.address), 10);
+    assert_eq!(usdc.balance(&collector), 0);
+    assert_eq!(token.balance(&collector), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Referral rewards
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_referral_reward_accrues_on_completion() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, _) = setup_test();
+    let agent = Address::generate(&env);
+    assert_eq!(
+        client.try_set_referral_share_bps(&admin, &10_001).unwrap_err().unwrap(),
+        EscrowError::InvalidSplitRatio
+    );
+    client.set_referral_share_bps(&admin, &5_000);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &Some(agent.clone()));
+    client.accept_order(&farmer, &order_id);
+    assert_eq!(client.get_referral_rewards(&agent, &token.address), 0);
+    client.confirm_receipt(&buyer, &order_id);
+
+    // Half of the 15 fee, rounded down, goes to the referrer.
+    assert_eq!(client.get_referral_rewards(&agent, &token.address), 7);
+    assert_eq!(token.balance(&collector), 8);
+
+    assert_eq!(client.claim_referral_rewards(&agent, &token.address), 7);
+    assert_eq!(token.balance(&agent), 7);
+    assert_eq!(client.get_referral_rewards(&agent, &token.address), 0);
+    assert_eq!(client.claim_referral_rewards(&agent, &token.address), 0);
+}
+
+#[test]
+fn test_referrer_must_not_be_an_order_party() {
+    let (env, client, buyer, farmer, _, token, _, admin, _, _) = setup_test();
+    client.set_referral_share_bps(&admin, &5_000);
+
+    for referrer in [buyer.clone(), farmer.clone()] {
+        let result =
+            client.try_create_order(&buyer, &farmer, &token.address, &500, &None, &Some(referrer));
+        assert_eq!(result.unwrap_err().unwrap(), EscrowError::BuyerCannotEqualFarmer);
+    }
+    assert_eq!(token.balance(&buyer), 1000);
+
+    let co_buyer = Address::generate(&env);
+    let co_buyers = Vec::from_array(&env, [buyer.clone(), co_buyer.clone()]);
+    let shares = Vec::from_array(&env, [300i128, 200i128]);
+    for referrer in [co_buyer.clone(), farmer.clone()] {
+        let result = client.try_create_split_order(
+            &buyer,
+            &farmer,
+            &token.address,
+            &co_buyers,
+            &shares,
+            &None,
+            &None,
+            &Some(referrer),
+        );
+        assert_eq!(result.unwrap_err().unwrap(), EscrowError::BuyerCannotEqualFarmer);
+    }
+    assert!(client.try_get_split_order(&1).is_err());
+
+    // Nor can the referrer join later by taking over a co-buyer's slot.
+    let referrer = Address::generate(&env);
+    let order_id = client.create_split_order(
+        &buyer,
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &shares,
+        &None,
+        &None,
+        &Some(referrer.clone()),
+    );
+    let result = client.try_transfer_split_share(&co_buyer, &referrer, &order_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::BuyerCannotEqualFarmer);
+}
+
+#[test]
+fn test_referral_reward_forfeited_when_buyer_prevails() {
+    let (env, client, buyer, farmer, collector, token, _, admin, _, _) = setup_test();
+    let agent = Address::generate(&env);
+    client.set_referral_share_bps(&admin, &5_000);
+
+    let order_id = client.create_order(&buyer, &farmer, &token.address, &500, &None, &Some(agent.clone()));
+    client.accept_order(&farmer, &order_id);
+    client.open_dispute(
+        &buyer,
+        &order_id,
+        &String::from_str(&env, "half the crates spoiled"),
+        &String::from_str(&env, "hash"),
+    );
+    client.resolve_dispute(&admin, &order_id, &DisputeResolution::Split(7_000));
+    finalize_ruling(&env, &client, order_id);
+
+    // The farmer's 30% of the 15 fee is collected without a referral cut.
+    assert_eq!(client.get_order_details(&order_id).status, OrderStatus::Completed);
+    assert_eq!(client.get_referral_rewards(&agent, &token.address), 0);
+    assert_eq!(token.balance(&collector), 5);
+
+    // A refunded split order forfeits the reward held back at activation.
+    let (env, client, farmer, co_buyers, token, contract_id, admin) = setup_split_test(2);
+    let agent = Address::generate(&env);
+    client.set_referral_share_bps(&admin, &5_000);
+    let order_id = client.create_split_order(
+        &co_buyers.get(0).unwrap(),
+        &farmer,
+        &token.address,
+        &co_buyers,
+        &Vec::from_array(&env, [500i128, 500i128]),
+        &None,
+        &None,
+        &Some(agent.clone()),
+    );
+    for co_buyer in co_buyers.iter() {
+        client.fund_split_order(&co_buyer, &order_id);
+    }
+    client.open_split_dispute(
+        &co_buyers.get(1).unwrap(),
+        &order_id,
+        &String::from_str(&env, "produce never arrived"),
+        &String::from_str(&env, "hash"),
+    );
+    client.resolve_split_dispute(&admin, &order_id, &DisputeResolution::Refund);
+
+    assert_eq!(client.get_referral_rewards(&agent, &token.address), 0);
+    assert_eq!(token.balance(&contract_id), 0);
+}
@@ -0,0 +1,585 @@
+#![no_std]
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, IntoVal, Map,
+    Symbol, TryFromVal, Val, Vec,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ListingError {
+    AlreadyInitialized = 1,
+    ContractNotInitialized = 2,
+    NotAdmin = 3,
+    ListingNotFound = 4,
+    NotListingFarmer = 5,
+    ListingNotActive = 6,
+    ListingExpired = 7,
+    InvalidPrice = 8,
+    InvalidQuantity = 9,
+    InvalidValidity = 10,
+    InsufficientStock = 11,
+    PriceAboveLimit = 12,
+    ArithmeticError = 13,
+    /// The escrow order was not opened through `take_offer`, or its stock
+    /// has already been returned.
+    UnknownOrder = 14,
+    /// The escrow order can still be delivered, so its stock stays taken.
+    OrderStillOpen = 15,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ListingStatus {
+    /// Open for buyers until `valid_until`, while stock remains.
+    Active,
+    /// Every unit has been taken; restocking via `update_listing` reopens it.
+    SoldOut,
+    /// Withdrawn by the farmer. Terminal.
+    Withdrawn,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Listing {
+    pub id: u64,
+    pub farmer: Address,
+    pub crop: Symbol,
+    /// Unit the price and quantity are quoted in (e.g. `kg`), carried over to
+    /// the escrow order created when the offer is taken.
+    pub unit: Symbol,
+    pub token: Address,
+    pub price_per_unit: i128,
+    pub available_quantity: i128,
+    /// Delivery window passed to the escrow order; `None` uses the escrow's
+    /// default.
+    pub delivery_window: Option<u64>,
+    pub created_at: u64,
+    /// Last ledger timestamp at which the offer may be taken.
+    pub valid_until: u64,
+    pub status: ListingStatus,
+}
+
+/// Stock an escrow order took from a listing, kept until it is either
+/// delivered or returned by `restock_unfilled_order`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TakenOffer {
+    /// Escrow deployment the order lives in.
+    pub escrow: Address,
+    pub listing_id: u64,
+    pub quantity: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    EscrowContract,
+    ListingCount,
+    Listing(u64),
+    FarmerListingCount(Address),
+    FarmerListingAt(Address, u64),
+    CropListingCount(Symbol),
+    CropListingAt(Symbol, u64),
+    /// `TakenOffer` behind an escrow order id.
+    TakenOffer(u64),
+}
+
+const TTL_THRESHOLD: u32 = 1000;
+const TTL_EXTEND_TO: u32 = 100_000;
+/// Upper bound on a single page returned by the listing views.
+const MAX_LISTING_PAGE: u32 = 50;
+
+fn read_admin(env: &Env) -> Result<Address, ListingError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ListingError::ContractNotInitialized)
+}
+
+fn read_escrow(env: &Env) -> Result<Address, ListingError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::EscrowContract)
+        .ok_or(ListingError::ContractNotInitialized)
+}
+
+fn read_listing(env: &Env, listing_id: u64) -> Result<Listing, ListingError> {
+    let key = DataKey::Listing(listing_id);
+    let listing: Listing = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ListingError::ListingNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    Ok(listing)
+}
+
+fn write_listing(env: &Env, listing: &Listing) {
+    let key = DataKey::Listing(listing.id);
+    env.storage().persistent().set(&key, listing);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+fn read_index_len(env: &Env, count_key: &DataKey) -> u64 {
+    env.storage().persistent().get(count_key).unwrap_or(0)
+}
+
+/// Appends `listing_id` to the index whose length lives at `count_key` and
+/// whose entries are keyed by `entry_key(position)`.
+fn push_index(env: &Env, count_key: DataKey, entry_key: impl Fn(u64) -> DataKey, listing_id: u64) {
+    let storage = env.storage().persistent();
+    let len = read_index_len(env, &count_key);
+    let entry = entry_key(len);
+    storage.set(&entry, &listing_id);
+    storage.extend_ttl(&entry, TTL_THRESHOLD, TTL_EXTEND_TO);
+    storage.set(&count_key, &(len + 1));
+    storage.extend_ttl(&count_key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Returns up to `limit` (capped at `MAX_LISTING_PAGE`) listings from the
+/// index window starting at `start`. With `active_only`, listings that are
+/// not currently takeable are skipped within that window, so a page may hold
+/// fewer than `limit` entries while later pages still have results.
+fn index_page(
+    env: &Env,
+    count_key: DataKey,
+    entry_key: impl Fn(u64) -> DataKey,
+    start: u64,
+    limit: u32,
+    active_only: bool,
+) -> Vec<Listing> {
+    let mut page = Vec::new(env);
+    let len = read_index_len(env, &count_key);
+    let end = start
+        .saturating_add(limit.min(MAX_LISTING_PAGE) as u64)
+        .min(len);
+    let now = env.ledger().timestamp();
+    for position in start..end {
+        let Some(listing_id) = env
+            .storage()
+            .persistent()
+            .get::<_, u64>(&entry_key(position))
+        else {
+            continue;
+        };
+        let Ok(listing) = read_listing(env, listing_id) else {
+            continue;
+        };
+        if active_only && (listing.status != ListingStatus::Active || now > listing.valid_until) {
+            continue;
+        }
+        page.push_back(listing);
+    }
+    page
+}
+
+fn validate_terms(
+    env: &Env,
+    price_per_unit: i128,
+    quantity: i128,
+    valid_until: u64,
+) -> Result<(), ListingError> {
+    if price_per_unit <= 0 {
+        return Err(ListingError::InvalidPrice);
+    }
+    if quantity < 0 {
+        return Err(ListingError::InvalidQuantity);
+    }
+    if valid_until <= env.ledger().timestamp() {
+        return Err(ListingError::InvalidValidity);
+    }
+    Ok(())
+}
+
+fn owned_listing(env: &Env, farmer: &Address, listing_id: u64) -> Result<Listing, ListingError> {
+    let listing = read_listing(env, listing_id)?;
+    if listing.farmer != *farmer {
+        return Err(ListingError::NotListingFarmer);
+    }
+    if listing.status == ListingStatus::Withdrawn {
+        return Err(ListingError::ListingNotActive);
+    }
+    Ok(listing)
+}
+
+mod escrow_client {
+    use super::*;
+
+    /// Opens a quantity-priced escrow order for `quantity` units of the
+    /// listing. Any escrow error aborts the whole call, so the stock taken
+    /// from the listing is rolled back along with it.
+    pub fn create_quantity_order(
+        env: &Env,
+        escrow: &Address,
+        buyer: &Address,
+        listing: &Listing,
+        quantity: i128,
+    ) -> u64 {
+        let func = Symbol::new(env, "create_quantity_order");
+        let mut args: Vec<Val> = Vec::new(env);
+        args.push_back(buyer.into_val(env));
+        args.push_back(listing.farmer.into_val(env));
+        args.push_back(listing.token.into_val(env));
+        args.push_back(quantity.into_val(env));
+        args.push_back(listing.unit.into_val(env));
+        args.push_back(listing.price_per_unit.into_val(env));
+        args.push_back(listing.delivery_window.into_val(env));
+        env.invoke_contract::<u64>(escrow, &func, args)
+    }
+
+    /// The escrow's `OrderStatus`, decoded by variant name.
+    #[contracttype]
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum EscrowOrderStatus {
+        Awaiting,
+        Pending,
+        Disputed,
+        Completed,
+        Refunded,
+    }
+
+    /// Units of escrow order `order_id` that will never be delivered, once
+    /// the order can take no more deliveries: it was refunded (rejected,
+    /// cancelled, left unaccepted or lost in a dispute) or its undelivered
+    /// remainder was refunded on expiry. `None` while it is still open.
+    pub fn undelivered_quantity(env: &Env, escrow: &Address, order_id: u64) -> Option<i128> {
+        let func = Symbol::new(env, "get_order_details");
+        let mut args: Vec<Val> = Vec::new(env);
+        args.push_back(order_id.into_val(env));
+        let order = env.invoke_contract::<Map<Symbol, Val>>(escrow, &func, args);
+        let field = |name: &str| order.get(Symbol::new(env, name));
+
+        let status = EscrowOrderStatus::try_from_val(env, &field("status")?).ok()?;
+        let remainder_refunded = bool::try_from_val(env, &field("remainder_refunded")?).ok()?;
+        if status != EscrowOrderStatus::Refunded && !remainder_refunded {
+            return None;
+        }
+        let quantity = i128::try_from_val(env, &field("quantity")?).ok()?;
+        let delivered = i128::try_from_val(env, &field("delivered_quantity")?).ok()?;
+        Some((quantity - delivered).max(0))
+    }
+}
+
+#[contract]
+pub struct ListingsContract;
+
+#[contractimpl]
+impl ListingsContract {
+    /// Binds the marketplace to the escrow contract that taken offers are
+    /// settled through.
+    pub fn initialize(env: Env, admin: Address, escrow: Address) -> Result<(), ListingError> {
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::Admin) {
+            return Err(ListingError::AlreadyInitialized);
+        }
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::EscrowContract, &escrow);
+        storage.set(&DataKey::ListingCount, &0u64);
+        Ok(())
+    }
+
+    /// Repoints taken offers at a new escrow deployment. Admin only.
+    pub fn set_escrow_contract(
+        env: Env,
+        admin: Address,
+        escrow: Address,
+    ) -> Result<(), ListingError> {
+        admin.require_auth();
+        if admin != read_admin(&env)? {
+            return Err(ListingError::NotAdmin);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::EscrowContract, &escrow);
+        Ok(())
+    }
+
+    pub fn get_escrow_contract(env: Env) -> Result<Address, ListingError> {
+        read_escrow(&env)
+    }
+
+    /// Publishes an offer of `quantity` `unit`s of `crop` at `price_per_unit`
+    /// in `token`, open until `valid_until`. Returns the listing id.
+    pub fn create_listing(
+        env: Env,
+        farmer: Address,
+        crop: Symbol,
+        unit: Symbol,
+        token: Address,
+        price_per_unit: i128,
+        quantity: i128,
+        valid_until: u64,
+        delivery_window: Option<u64>,
+    ) -> Result<u64, ListingError> {
+        farmer.require_auth();
+        read_escrow(&env)?;
+
+        validate_terms(&env, price_per_unit, quantity, valid_until)?;
+        if quantity == 0 {
+            return Err(ListingError::InvalidQuantity);
+        }
+
+        let instance = env.storage().instance();
+        let listing_id: u64 = instance.get(&DataKey::ListingCount).unwrap_or(0);
+        instance.set(
+            &DataKey::ListingCount,
+            &listing_id
+                .checked_add(1)
+                .ok_or(ListingError::ArithmeticError)?,
+        );
+
+        let listing = Listing {
+            id: listing_id,
+            farmer: farmer.clone(),
+            crop: crop.clone(),
+            unit,
+            token,
+            price_per_unit,
+            available_quantity: quantity,
+            delivery_window,
+            created_at: env.ledger().timestamp(),
+            valid_until,
+            status: ListingStatus::Active,
+        };
+        write_listing(&env, &listing);
+        push_index(
+            &env,
+            DataKey::FarmerListingCount(farmer.clone()),
+            |i| DataKey::FarmerListingAt(farmer.clone(), i),
+            listing_id,
+        );
+        push_index(
+            &env,
+            DataKey::CropListingCount(crop.clone()),
+            |i| DataKey::CropListingAt(crop.clone(), i),
+            listing_id,
+        );
+
+        env.events().publish(
+            (symbol_short!("listing"), symbol_short!("created")),
+            (listing_id, farmer, crop, price_per_unit, quantity),
+        );
+        Ok(listing_id)
+    }
+
+    /// Replaces the price, stock and validity of a listing the farmer still
+    /// holds open. Restocking a sold-out listing reopens it; setting the
+    /// quantity to zero marks it sold out.
+    pub fn update_listing(
+        env: Env,
+        farmer: Address,
+        listing_id: u64,
+        price_per_unit: i128,
+        available_quantity: i128,
+        valid_until: u64,
+    ) -> Result<(), ListingError> {
+        farmer.require_auth();
+        let mut listing = owned_listing(&env, &farmer, listing_id)?;
+        validate_terms(&env, price_per_unit, available_quantity, valid_until)?;
+
+        listing.price_per_unit = price_per_unit;
+        listing.available_quantity = available_quantity;
+        listing.valid_until = valid_until;
+        listing.status = if available_quantity == 0 {
+            ListingStatus::SoldOut
+        } else {
+            ListingStatus::Active
+        };
+        write_listing(&env, &listing);
+
+        env.events().publish(
+            (symbol_short!("listing"), symbol_short!("updated")),
+            (listing_id, price_per_unit, available_quantity, valid_until),
+        );
+        Ok(())
+    }
+
+    /// Takes a listing off the market. Orders already created from it are
+    /// unaffected and continue through escrow.
+    pub fn withdraw_listing(
+        env: Env,
+        farmer: Address,
+        listing_id: u64,
+    ) -> Result<(), ListingError> {
+        farmer.require_auth();
+        let mut listing = owned_listing(&env, &farmer, listing_id)?;
+
+        listing.status = ListingStatus::Withdrawn;
+        write_listing(&env, &listing);
+
+        env.events().publish(
+            (symbol_short!("listing"), symbol_short!("withdrawn")),
+            listing_id,
+        );
+        Ok(())
+    }
+
+    /// Buys `quantity` units from a listing: the stock is decremented and an
+    /// escrow order is opened for the buyer in the same call, so either both
+    /// happen or neither does. `max_price_per_unit` guards the buyer against
+    /// a price update landing first. Stock the order ends up not taking is
+    /// returned through `restock_unfilled_order`. Returns the escrow order id.
+    pub fn take_offer(
+        env: Env,
+        buyer: Address,
+        listing_id: u64,
+        quantity: i128,
+        max_price_per_unit: i128,
+    ) -> Result<u64, ListingError> {
+        buyer.require_auth();
+        let escrow = read_escrow(&env)?;
+        let mut listing = read_listing(&env, listing_id)?;
+
+        if listing.status != ListingStatus::Active {
+            return Err(ListingError::ListingNotActive);
+        }
+        if env.ledger().timestamp() > listing.valid_until {
+            return Err(ListingError::ListingExpired);
+        }
+        if quantity <= 0 {
+            return Err(ListingError::InvalidQuantity);
+        }
+        if quantity > listing.available_quantity {
+            return Err(ListingError::InsufficientStock);
+        }
+        if listing.price_per_unit > max_price_per_unit {
+            return Err(ListingError::PriceAboveLimit);
+        }
+
+        listing.available_quantity = listing
+            .available_quantity
+            .checked_sub(quantity)
+            .ok_or(ListingError::ArithmeticError)?;
+        if listing.available_quantity == 0 {
+            listing.status = ListingStatus::SoldOut;
+        }
+        write_listing(&env, &listing);
+
+        let order_id =
+            escrow_client::create_quantity_order(&env, &escrow, &buyer, &listing, quantity);
+        let key = DataKey::TakenOffer(order_id);
+        env.storage().persistent().set(
+            &key,
+            &TakenOffer {
+                escrow,
+                listing_id,
+                quantity,
+            },
+        );
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+
+        env.events().publish(
+            (symbol_short!("listing"), symbol_short!("taken")),
+            (listing_id, buyer, quantity, order_id),
+        );
+        Ok(order_id)
+    }
+
+    /// Returns the undelivered stock of an escrow order opened by
+    /// `take_offer` to its listing once the escrow order can no longer be
+    /// filled: the farmer rejected it, the buyer cancelled it or reclaimed
+    /// it after the acceptance window or delivery deadline lapsed, or a
+    /// dispute refunded it. A sold-out listing reopens; a withdrawn one stays
+    /// withdrawn. Anyone may call it, once per order. Returns the units put
+    /// back on the listing.
+    pub fn restock_unfilled_order(env: Env, order_id: u64) -> Result<i128, ListingError> {
+        let key = DataKey::TakenOffer(order_id);
+        let taken: TakenOffer = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ListingError::UnknownOrder)?;
+        let undelivered = escrow_client::undelivered_quantity(&env, &taken.escrow, order_id)
+            .ok_or(ListingError::OrderStillOpen)?
+            .min(taken.quantity);
+        env.storage().persistent().remove(&key);
+
+        let mut listing = read_listing(&env, taken.listing_id)?;
+        if listing.status == ListingStatus::Withdrawn || undelivered == 0 {
+            return Ok(0);
+        }
+        listing.available_quantity = listing
+            .available_quantity
+            .checked_add(undelivered)
+            .ok_or(ListingError::ArithmeticError)?;
+        listing.status = ListingStatus::Active;
+        write_listing(&env, &listing);
+
+        env.events().publish(
+            (symbol_short!("listing"), symbol_short!("restockd")),
+            (taken.listing_id, order_id, undelivered),
+        );
+        Ok(undelivered)
+    }
+
+    pub fn get_taken_offer(env: Env, order_id: u64) -> Option<TakenOffer> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TakenOffer(order_id))
+    }
+
+    pub fn get_listing(env: Env, listing_id: u64) -> Result<Listing, ListingError> {
+        read_listing(&env, listing_id)
+    }
+
+    pub fn get_listing_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ListingCount)
+            .unwrap_or(0)
+    }
+
+    /// Pages through a farmer's listings in creation order.
+    pub fn get_listings_by_farmer(
+        env: Env,
+        farmer: Address,
+        start: u64,
+        limit: u32,
+        active_only: bool,
+    ) -> Vec<Listing> {
+        index_page(
+            &env,
+            DataKey::FarmerListingCount(farmer.clone()),
+            |i| DataKey::FarmerListingAt(farmer.clone(), i),
+            start,
+            limit,
+            active_only,
+        )
+    }
+
+    pub fn get_farmer_listing_count(env: Env, farmer: Address) -> u64 {
+        read_index_len(&env, &DataKey::FarmerListingCount(farmer))
+    }
+
+    /// Pages through every listing for `crop` in creation order.
+    pub fn get_listings_by_crop(
+        env: Env,
+        crop: Symbol,
+        start: u64,
+        limit: u32,
+        active_only: bool,
+    ) -> Vec<Listing> {
+        index_page(
+            &env,
+            DataKey::CropListingCount(crop.clone()),
+            |i| DataKey::CropListingAt(crop.clone(), i),
+            start,
+            limit,
+            active_only,
+        )
+    }
+
+    pub fn get_crop_listing_count(env: Env, crop: Symbol) -> u64 {
+        read_index_len(&env, &DataKey::CropListingCount(crop))
+    }
+}
+
+mod test;
@@ -0,0 +1,294 @@
+#![cfg(test)]
+
+use super::*;
+use escrow::{EscrowContract, EscrowContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Env, Vec,
+};
+
+struct Setup {
+    env: Env,
+    client: ListingsContractClient<'static>,
+    escrow: EscrowContractClient<'static>,
+    token: token::Client<'static>,
+    farmer: Address,
+    buyer: Address,
+}
+
+fn setup_test() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000_000);
+
+    let admin = Address::generate(&env);
+    let farmer = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let xlm_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
+    let xlm_client = token::Client::new(&env, &xlm_contract.address());
+    token::StellarAssetClient::new(&env, &xlm_contract.address()).mint(&buyer, &10_000);
+    let usdc_contract = env.register_stellar_asset_contract_v2(token_admin);
+
+    let escrow_id = env.register(EscrowContract, ());
+    let escrow = EscrowContractClient::new(&env, &escrow_id);
+    let mut supported_tokens = Vec::new(&env);
+    supported_tokens.push_back(xlm_contract.address());
+    supported_tokens.push_back(usdc_contract.address());
+    escrow.initialize(&admin, &Address::generate(&env), &supported_tokens);
+
+    let contract_id = env.register(ListingsContract, ());
+    let client = ListingsContractClient::new(&env, &contract_id);
+    client.initialize(&admin, &escrow_id);
+
+    Setup {
+        env,
+        client,
+        escrow,
+        token: xlm_client,
+        farmer,
+        buyer,
+    }
+}
+
+fn list(s: &Setup, crop: &str, quantity: i128) -> u64 {
+    s.client.create_listing(
+        &s.farmer,
+        &Symbol::new(&s.env, crop),
+        &symbol_short!("kg"),
+        &s.token.address,
+        &10,
+        &quantity,
+        &(s.env.ledger().timestamp() + 86_400),
+        &None,
+    )
+}
+
+#[test]
+fn test_take_offer_decrements_stock_and_opens_escrow_order() {
+    let s = setup_test();
+    let listing_id = list(&s, "maize", 100);
+
+    let order_id = s.client.take_offer(&s.buyer, &listing_id, &40, &10);
+
+    let listing = s.client.get_listing(&listing_id);
+    assert_eq!(listing.available_quantity, 60);
+    assert_eq!(listing.status, ListingStatus::Active);
+
+    let order = s.escrow.get_order_details(&order_id);
+    assert_eq!(order.buyer, s.buyer);
+    assert_eq!(order.farmer, s.farmer);
+    assert_eq!(order.quantity, 40);
+    assert_eq!(order.unit_price, 10);
+    assert_eq!(s.token.balance(&s.buyer), 10_000 - 400);
+
+    s.client.take_offer(&s.buyer, &listing_id, &60, &10);
+    assert_eq!(
+        s.client.get_listing(&listing_id).status,
+        ListingStatus::SoldOut
+    );
+    assert_eq!(
+        s.client.try_take_offer(&s.buyer, &listing_id, &1, &10),
+        Err(Ok(ListingError::ListingNotActive))
+    );
+}
+
+#[test]
+fn test_take_offer_rejects_invalid_requests() {
+    let s = setup_test();
+    let listing_id = list(&s, "maize", 100);
+
+    assert_eq!(
+        s.client.try_take_offer(&s.buyer, &listing_id, &101, &10),
+        Err(Ok(ListingError::InsufficientStock))
+    );
+    assert_eq!(
+        s.client.try_take_offer(&s.buyer, &listing_id, &0, &10),
+        Err(Ok(ListingError::InvalidQuantity))
+    );
+    assert_eq!(
+        s.client.try_take_offer(&s.buyer, &listing_id, &10, &9),
+        Err(Ok(ListingError::PriceAboveLimit))
+    );
+
+    assert!(s
+        .client
+        .try_take_offer(&s.buyer, &listing_id, &100, &10)
+        .is_ok());
+
+    // The buyer cannot afford the order: the escrow call fails and the stock
+    // decrement is rolled back with it.
+    let listing_id = list(&s, "maize", 5_000);
+    assert!(s
+        .client
+        .try_take_offer(&s.buyer, &listing_id, &5_000, &10)
+        .is_err());
+    assert_eq!(s.client.get_listing(&listing_id).available_quantity, 5_000);
+
+    s.env.ledger().set_timestamp(1_000_000 + 86_401);
+    assert_eq!(
+        s.client.try_take_offer(&s.buyer, &listing_id, &1, &10),
+        Err(Ok(ListingError::ListingExpired))
+    );
+}
+
+#[test]
+fn test_update_and_withdraw_listing() {
+    let s = setup_test();
+    let listing_id = list(&s, "maize", 10);
+    let other = Address::generate(&s.env);
+    let valid_until = s.env.ledger().timestamp() + 1_000;
+
+    assert_eq!(
+        s.client
+            .try_update_listing(&other, &listing_id, &12, &10, &valid_until),
+        Err(Ok(ListingError::NotListingFarmer))
+    );
+    assert_eq!(
+        s.client
+            .try_update_listing(&s.farmer, &listing_id, &0, &10, &valid_until),
+        Err(Ok(ListingError::InvalidPrice))
+    );
+
+    s.client.take_offer(&s.buyer, &listing_id, &10, &10);
+    s.client
+        .update_listing(&s.farmer, &listing_id, &12, &20, &valid_until);
+    let listing = s.client.get_listing(&listing_id);
+    assert_eq!(listing.status, ListingStatus::Active);
+    assert_eq!(listing.price_per_unit, 12);
+    assert_eq!(listing.available_quantity, 20);
+
+    s.client.withdraw_listing(&s.farmer, &listing_id);
+    assert_eq!(
+        s.client.get_listing(&listing_id).status,
+        ListingStatus::Withdrawn
+    );
+    assert_eq!(
+        s.client.try_take_offer(&s.buyer, &listing_id, &1, &12),
+        Err(Ok(ListingError::ListingNotActive))
+    );
+    assert_eq!(
+        s.client
+            .try_update_listing(&s.farmer, &listing_id, &12, &20, &valid_until),
+        Err(Ok(ListingError::ListingNotActive))
+    );
+}
+
+#[test]
+fn test_listing_pages_by_farmer_and_crop() {
+    let s = setup_test();
+    let maize = Symbol::new(&s.env, "maize");
+    let first = list(&s, "maize", 10);
+    list(&s, "cassava", 10);
+    let third = list(&s, "maize", 10);
+    s.client.withdraw_listing(&s.farmer, &first);
+
+    assert_eq!(s.client.get_farmer_listing_count(&s.farmer), 3);
+    assert_eq!(s.client.get_crop_listing_count(&maize), 2);
+
+    let page = s.client.get_listings_by_farmer(&s.farmer, &1, &5, &false);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(1).unwrap().id, third);
+
+    let all = s.client.get_listings_by_crop(&maize, &0, &10, &false);
+    assert_eq!(all.len(), 2);
+    let active = s.client.get_listings_by_crop(&maize, &0, &10, &true);
+    assert_eq!(active.len(), 1);
+    assert_eq!(active.get(0).unwrap().id, third);
+
+    s.env.ledger().set_timestamp(1_000_000 + 86_401);
+    assert_eq!(
+        s.client
+            .get_listings_by_farmer(&s.farmer, &0, &10, &true)
+            .len(),
+        0
+    );
+}
+
+#[test]
+fn test_unfilled_orders_return_their_stock() {
+    let s = setup_test();
+    let listing_id = list(&s, "maize", 100);
+    let rejected = s.client.take_offer(&s.buyer, &listing_id, &60, &10);
+    let lapsed = s.client.take_offer(&s.buyer, &listing_id, &40, &10);
+    assert_eq!(
+        s.client.get_listing(&listing_id).status,
+        ListingStatus::SoldOut
+    );
+
+    // Stock stays taken while the farmer can still accept.
+    assert_eq!(
+        s.client.try_restock_unfilled_order(&rejected),
+        Err(Ok(ListingError::OrderStillOpen))
+    );
+
+    s.escrow.reject_order(&s.farmer, &rejected);
+    assert_eq!(s.client.restock_unfilled_order(&rejected), 60);
+    let listing = s.client.get_listing(&listing_id);
+    assert_eq!(listing.available_quantity, 60);
+    assert_eq!(listing.status, ListingStatus::Active);
+    assert_eq!(
+        s.client.try_restock_unfilled_order(&rejected),
+        Err(Ok(ListingError::UnknownOrder))
+    );
+
+    // An order the farmer never accepts comes back once the buyer reclaims
+    // it after the acceptance window.
+    s.env.ledger().set_timestamp(1_000_000 + 48 * 60 * 60 + 1);
+    assert_eq!(
+        s.client.try_restock_unfilled_order(&lapsed),
+        Err(Ok(ListingError::OrderStillOpen))
+    );
+    s.escrow.refund_expired_order(&s.buyer, &lapsed);
+    assert_eq!(s.client.restock_unfilled_order(&lapsed), 40);
+    assert_eq!(s.client.get_listing(&listing_id).available_quantity, 100);
+    assert_eq!(s.token.balance(&s.buyer), 10_000);
+}
+
+#[test]
+fn test_refund_does_not_reopen_withdrawn_listing() {
+    let s = setup_test();
+    let listing_id = list(&s, "maize", 100);
+    let order_id = s.client.take_offer(&s.buyer, &listing_id, &30, &10);
+    s.escrow.accept_order(&s.farmer, &order_id);
+    assert_eq!(s.client.get_taken_offer(&order_id).unwrap().quantity, 30);
+
+    // A withdrawn listing is not reopened by a refund.
+    s.escrow.cancel_order(&s.buyer, &order_id);
+    s.client.withdraw_listing(&s.farmer, &listing_id);
+    assert_eq!(s.client.restock_unfilled_order(&order_id), 0);
+    let listing = s.client.get_listing(&listing_id);
+    assert_eq!(listing.status, ListingStatus::Withdrawn);
+    assert_eq!(listing.available_quantity, 70);
+    assert_eq!(s.client.get_taken_offer(&order_id), None);
+}
+
+#[test]
+fn test_escrow_failure_rolls_back_stock() {
+    let s = setup_test();
+    let unsupported = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    token::StellarAssetClient::new(&s.env, &unsupported).mint(&s.buyer, &10_000);
+    let listing_id = s.client.create_listing(
+        &s.farmer,
+        &Symbol::new(&s.env, "maize"),
+        &symbol_short!("kg"),
+        &unsupported,
+        &10,
+        &100,
+        &(s.env.ledger().timestamp() + 86_400),
+        &None,
+    );
+
+    assert!(s
+        .client
+        .try_take_offer(&s.buyer, &listing_id, &100, &10)
+        .is_err());
+    let listing = s.client.get_listing(&listing_id);
+    assert_eq!(listing.available_quantity, 100);
+    assert_eq!(listing.status, ListingStatus::Active);
+    assert_eq!(s.escrow.get_order_count(), 0);
+}